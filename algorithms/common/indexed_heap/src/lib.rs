@@ -0,0 +1,252 @@
+// An indexed d-ary min-heap, shared by the shortest path and spanning tree code.
+//
+// Items are the numbers 0..capacity, and each item is in the heap at most once. Because the
+// heap knows where every item is, the key of an item already in the heap can be lowered in
+// place, instead of pushing a second copy and skipping the stale one later like you have to
+// with std::collections::BinaryHeap. The heap therefore never holds more than capacity
+// entries.
+
+// The number of children of each node if none is given. Wider heaps are shallower, which makes
+// decrease_key cheaper at the cost of a slightly more expensive pop.
+pub const DEFAULT_ARITY: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct IndexedHeap<K> {
+    arity: usize,
+    // heap[i] is the item at position i. The children of position i are at positions
+    // arity * i + 1 up to arity * i + arity.
+    heap: Vec<usize>,
+    // keys[item] is the key of item if it's in the heap.
+    keys: Vec<Option<K>>,
+    // positions[item] is the position of item in heap if it's in the heap.
+    positions: Vec<Option<usize>>,
+}
+
+impl<K: Ord> IndexedHeap<K> {
+    // Returns an empty heap for the items 0..capacity with DEFAULT_ARITY children per node.
+    pub fn new(capacity: usize) -> IndexedHeap<K> {
+        IndexedHeap::with_arity(capacity, DEFAULT_ARITY)
+    }
+
+    // Returns an empty heap for the items 0..capacity with arity children per node. Panics if
+    // arity is less than 2.
+    pub fn with_arity(capacity: usize, arity: usize) -> IndexedHeap<K> {
+        assert!(arity >= 2, "A heap needs at least two children per node");
+        IndexedHeap {
+            arity,
+            heap: Vec::with_capacity(capacity),
+            keys: (0..capacity).map(|_| None).collect(),
+            positions: vec![None; capacity],
+        }
+    }
+
+    // Returns the number of items in the heap.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    // Returns true if there are no items in the heap.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    // Returns the number of different items the heap can hold.
+    pub fn capacity(&self) -> usize {
+        self.positions.len()
+    }
+
+    // Returns true if item is in the heap.
+    pub fn contains(&self, item: usize) -> bool {
+        self.positions.get(item).is_some_and(Option::is_some)
+    }
+
+    // Returns the key of item, or None if item isn't in the heap.
+    pub fn key(&self, item: usize) -> Option<&K> {
+        self.keys.get(item)?.as_ref()
+    }
+
+    // Returns the item with the smallest key and its key without removing it, or None if the
+    // heap is empty.
+    pub fn peek(&self) -> Option<(usize, &K)> {
+        let &item = self.heap.first()?;
+        Some((item, self.key(item)?))
+    }
+
+    // Adds item to the heap with the given key. Panics if item is already in the heap or isn't
+    // less than capacity.
+    pub fn push(&mut self, item: usize, key: K) {
+        assert!(!self.contains(item), "Item {} is already in the heap", item);
+        self.keys[item] = Some(key);
+        self.positions[item] = Some(self.heap.len());
+        self.heap.push(item);
+        self.sift_up(self.heap.len() - 1);
+    }
+
+    // Lowers the key of item, which must already be in the heap. Panics if item isn't in the
+    // heap or key is larger than its current key.
+    pub fn decrease_key(&mut self, item: usize, key: K) {
+        let position =
+            self.positions[item].expect("Can only decrease the key of items in the heap");
+        assert!(
+            Some(&key) <= self.keys[item].as_ref(),
+            "decrease_key can't increase a key"
+        );
+        self.keys[item] = Some(key);
+        self.sift_up(position);
+    }
+
+    // Adds item with the given key if it isn't in the heap, or lowers its key if the new key is
+    // smaller. Returns true if the heap changed. This is the usual way to relax an edge.
+    pub fn push_or_decrease(&mut self, item: usize, key: K) -> bool {
+        match self.key(item) {
+            None => self.push(item, key),
+            Some(current) if key < *current => self.decrease_key(item, key),
+            Some(_) => return false,
+        }
+        true
+    }
+
+    // Removes the item with the smallest key and returns it with its key, or None if the heap
+    // is empty.
+    pub fn pop(&mut self) -> Option<(usize, K)> {
+        let last = self.heap.len().checked_sub(1)?;
+        self.swap(0, last);
+        let item = self.heap.pop()?;
+        self.positions[item] = None;
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+        Some((item, self.keys[item].take()?))
+    }
+
+    // Moves the item at position up until its parent's key is no larger than its own.
+    fn sift_up(&mut self, mut position: usize) {
+        while position > 0 {
+            let parent = (position - 1) / self.arity;
+            if self.key_at(parent) <= self.key_at(position) {
+                break;
+            }
+            self.swap(parent, position);
+            position = parent;
+        }
+    }
+
+    // Moves the item at position down until none of its children has a smaller key.
+    fn sift_down(&mut self, mut position: usize) {
+        loop {
+            let first_child = self.arity * position + 1;
+            let last_child = (first_child + self.arity).min(self.heap.len());
+            let smallest_child = match (first_child..last_child)
+                .min_by(|&a, &b| self.key_at(a).cmp(self.key_at(b)))
+            {
+                Some(child) => child,
+                None => break,
+            };
+            if self.key_at(position) <= self.key_at(smallest_child) {
+                break;
+            }
+            self.swap(position, smallest_child);
+            position = smallest_child;
+        }
+    }
+
+    fn key_at(&self, position: usize) -> &K {
+        self.keys[self.heap[position]]
+            .as_ref()
+            .expect("Every item in the heap has a key")
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a]] = Some(a);
+        self.positions[self.heap[b]] = Some(b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::XorShift;
+
+    fn drain<K: Ord>(heap: &mut IndexedHeap<K>) -> Vec<(usize, K)> {
+        std::iter::from_fn(|| heap.pop()).collect()
+    }
+
+    #[test]
+    fn test_push_and_pop() {
+        let mut heap = IndexedHeap::new(5);
+        assert!(heap.is_empty());
+        for (item, key) in [(3, 30), (0, 50), (4, 10), (1, 40)] {
+            heap.push(item, key);
+        }
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.capacity(), 5);
+        assert_eq!(heap.peek(), Some((4, &10)));
+        assert!(heap.contains(1));
+        assert!(!heap.contains(2));
+        assert!(!heap.contains(7));
+        assert_eq!(heap.key(3), Some(&30));
+        assert_eq!(heap.key(2), None);
+        assert_eq!(drain(&mut heap), vec![(4, 10), (3, 30), (1, 40), (0, 50)]);
+        assert_eq!(heap.pop(), None);
+
+        // Items can go back in once they've been popped.
+        heap.push(4, 1);
+        assert_eq!(heap.pop(), Some((4, 1)));
+    }
+
+    #[test]
+    fn test_decrease_key() {
+        let mut heap = IndexedHeap::with_arity(4, 2);
+        for (item, key) in [(0, 10), (1, 20), (2, 30), (3, 40)] {
+            heap.push(item, key);
+        }
+        heap.decrease_key(3, 5);
+        assert!(heap.push_or_decrease(2, 15));
+        assert!(!heap.push_or_decrease(1, 25));
+        assert_eq!(heap.len(), 4);
+        assert_eq!(drain(&mut heap), vec![(3, 5), (0, 10), (2, 15), (1, 20)]);
+        assert!(heap.push_or_decrease(1, 25));
+        assert_eq!(heap.pop(), Some((1, 25)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_push_twice() {
+        let mut heap = IndexedHeap::new(2);
+        heap.push(1, 1);
+        heap.push(1, 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decrease_key_increasing() {
+        let mut heap = IndexedHeap::new(2);
+        heap.push(1, 1);
+        heap.decrease_key(1, 2);
+    }
+
+    #[test]
+    fn test_matches_sorting() {
+        // Random keys and decreases, checked against sorting the final keys.
+        let mut rng = XorShift::new(0x2545_f491_4f6c_dd1d);
+        for arity in 2..=5 {
+            let mut heap = IndexedHeap::with_arity(200, arity);
+            let mut keys: Vec<Option<u64>> = vec![None; 200];
+            for _ in 0..1000 {
+                let (item, key) = (rng.below(200), rng.below(10_000) as u64);
+                heap.push_or_decrease(item, key);
+                keys[item] = Some(keys[item].map_or(key, |current| current.min(key)));
+            }
+            let mut expected: Vec<(u64, usize)> = keys
+                .iter()
+                .enumerate()
+                .filter_map(|(item, key)| Some(((*key)?, item)))
+                .collect();
+            expected.sort_unstable();
+            let popped: Vec<u64> = drain(&mut heap).into_iter().map(|(_, key)| key).collect();
+            let expected_keys: Vec<u64> = expected.into_iter().map(|(key, _)| key).collect();
+            assert_eq!(popped, expected_keys);
+        }
+    }
+}
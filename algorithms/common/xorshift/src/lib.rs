@@ -0,0 +1,63 @@
+// A small xorshift random number generator, shared by the homeworks so that Karger's algorithm
+// and the randomized tests don't need an external crate and can be seeded for reproducible
+// results. It's fast and good enough for shuffling and generating test graphs, but it's not
+// meant for anything that needs real randomness.
+
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> XorShift {
+        // xorshift gets stuck at zero, so nudge that seed somewhere else.
+        XorShift(if seed == 0 {
+            0x2545_f491_4f6c_dd1d
+        } else {
+            seed
+        })
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // Returns a number in 0..n.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    // Shuffles items uniformly at random with the Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_seed() {
+        let mut rng = XorShift::new(0);
+        assert_ne!(rng.next_u64(), 0);
+        assert_eq!(
+            XorShift::new(0).next_u64(),
+            XorShift::new(0x2545_f491_4f6c_dd1d).next_u64()
+        );
+    }
+
+    #[test]
+    fn test_below_and_shuffle() {
+        let mut rng = XorShift::new(1);
+        assert!((0..1000).all(|_| rng.below(7) < 7));
+
+        let mut items: Vec<usize> = (0..100).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..100).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..100).collect::<Vec<_>>());
+    }
+}
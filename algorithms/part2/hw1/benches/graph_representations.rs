@@ -0,0 +1,59 @@
+// Compares building and running SCC on an AdjacencyList against a CsrGraph. Uses the assignment
+// input hw1_SCC.txt if it's present, and a random graph of about the same size otherwise.
+//
+// Run with `cargo bench`.
+use hw1::adjacency_list::AdjacencyList;
+use hw1::csr_graph::CsrGraph;
+use hw1::edge_list::EdgeList;
+use hw1::graph::Graph;
+use hw1::strongly_connected::{Kosaraju, PathBased, StronglyConnected, Tarjan};
+use std::{path::Path, time::Instant};
+use xorshift::XorShift;
+
+const SCC_FILE: &str = "hw1_SCC.txt";
+
+fn random_edge_list(num_vertices: usize, num_edges: usize) -> EdgeList {
+    let mut rng = XorShift::new(0);
+    let mut next = || rng.below(num_vertices) as i32 + 1;
+    EdgeList::new((0..num_edges).map(|_| [next(), next()]).collect())
+}
+
+// Runs f once and prints how long it took.
+fn time<T>(label: &str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    println!("{:<40} {:>10.2?}", label, start.elapsed());
+    result
+}
+
+fn bench_representation<G: Graph>(name: &str, build: impl FnOnce() -> G) {
+    let graph = time(&format!("{name}: build"), build);
+    let algorithms: [(&str, &dyn StronglyConnected<G>); 3] = [
+        ("kosaraju", &Kosaraju),
+        ("tarjan", &Tarjan),
+        ("path-based", &PathBased),
+    ];
+    for (algorithm_name, algorithm) in algorithms {
+        let components = time(&format!("{name}: {algorithm_name} scc"), || {
+            algorithm.scc(&graph)
+        });
+        assert!(components.iter().map(Vec::len).sum::<usize>() == graph.num_nodes());
+    }
+}
+
+fn main() {
+    let edge_list = if Path::new(SCC_FILE).exists() {
+        time("parse hw1_SCC.txt", || {
+            EdgeList::parse_edge_list(SCC_FILE).unwrap()
+        })
+    } else {
+        println!("{SCC_FILE} not found, using a random graph instead");
+        random_edge_list(875_714, 5_105_043)
+    };
+    println!("{} edges", edge_list.edges.len());
+
+    bench_representation("AdjacencyList", || {
+        AdjacencyList::from_edge_list(&edge_list)
+    });
+    bench_representation("CsrGraph", || CsrGraph::from_edge_list(&edge_list));
+}
@@ -0,0 +1,325 @@
+use crate::adjacency_list::AdjacencyList;
+use crate::graph::Graph;
+use std::collections::HashMap;
+
+// The result of analysing a graph as an undirected graph. Edges are written with the smaller
+// vertex first, and everything is sorted so the result doesn't depend on HashMap ordering.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Biconnectivity {
+    // Edges whose removal disconnects their endpoints from each other.
+    pub bridges: Vec<[i32; 2]>,
+    // Vertices whose removal disconnects some of the remaining vertices from each other.
+    pub articulation_points: Vec<i32>,
+    // The edges of each biconnected component, i.e. each maximal set of edges in which any two
+    // edges lie on a common simple cycle. A bridge is a component on its own.
+    pub components: Vec<Vec<[i32; 2]>>,
+}
+
+impl AdjacencyList {
+    // Finds the bridges, articulation points and biconnected components of the graph, treating
+    // every edge as undirected. An edge listed in both directions, or several times, counts as
+    // a single edge, and self loops are ignored since they can't disconnect anything. Uses the
+    // low-link values of an iterative DFS, so long paths don't overflow the stack.
+    pub fn biconnectivity(&self) -> Biconnectivity {
+        let neighbours = self.undirected_neighbours();
+        let mut bridges: Vec<[i32; 2]> = vec![];
+        let mut articulation_points: Vec<i32> = vec![];
+        let mut components: Vec<Vec<[i32; 2]>> = vec![];
+
+        // discovered[v] is when the DFS first reached v, and low[v] is the earliest discovery
+        // time reachable from the subtree of v using at most one back edge.
+        let mut discovered: HashMap<i32, usize> = HashMap::with_capacity(self.num_nodes());
+        let mut low: HashMap<i32, usize> = HashMap::with_capacity(self.num_nodes());
+        // The edges seen since the start of the biconnected component we're currently in.
+        let mut edges: Vec<[i32; 2]> = vec![];
+
+        for root in self.sorted_vertices() {
+            if discovered.contains_key(&root) {
+                continue;
+            }
+            discovered.insert(root, discovered.len());
+            low.insert(root, discovered[&root]);
+            let mut root_children = 0;
+            // Each entry holds a vertex on the current DFS path, its parent, and the
+            // neighbours we haven't looked at yet.
+            let mut todo: Vec<(i32, Option<i32>, &[i32])> = vec![(root, None, &neighbours[&root])];
+
+            while let Some((vertex, parent, next_vertices)) = todo.last_mut() {
+                let (vertex, parent) = (*vertex, *parent);
+                match *next_vertices {
+                    &[next_vertex, ref rest_vertices @ ..] => {
+                        *next_vertices = rest_vertices;
+                        if Some(next_vertex) == parent {
+                            continue;
+                        }
+                        match discovered.get(&next_vertex) {
+                            None => {
+                                let time = discovered.len();
+                                discovered.insert(next_vertex, time);
+                                low.insert(next_vertex, time);
+                                edges.push([vertex, next_vertex]);
+                                todo.push((next_vertex, Some(vertex), &neighbours[&next_vertex]));
+                            }
+                            // A back edge to an ancestor. Back edges to descendants were
+                            // already handled from the other end.
+                            Some(&time) if time < discovered[&vertex] => {
+                                let vertex_low = low.get_mut(&vertex).unwrap();
+                                *vertex_low = (*vertex_low).min(time);
+                                edges.push([vertex, next_vertex]);
+                            }
+                            Some(_) => {}
+                        }
+                    }
+                    [] => {
+                        todo.pop();
+                        let parent = match parent {
+                            Some(parent) => parent,
+                            None => continue,
+                        };
+                        let vertex_low = low[&vertex];
+                        let parent_low = low.get_mut(&parent).unwrap();
+                        *parent_low = (*parent_low).min(vertex_low);
+
+                        if vertex_low > discovered[&parent] {
+                            bridges.push(sorted_edge([parent, vertex]));
+                        }
+                        if vertex_low >= discovered[&parent] {
+                            // Nothing below vertex reaches above parent, so the edges added
+                            // since parent -> vertex make up a whole component.
+                            let start = edges
+                                .iter()
+                                .rposition(|&edge| edge == [parent, vertex])
+                                .expect("The tree edge to vertex should be on the edge stack");
+                            let mut component: Vec<[i32; 2]> =
+                                edges.drain(start..).map(sorted_edge).collect();
+                            component.sort_unstable();
+                            components.push(component);
+
+                            if parent != root {
+                                articulation_points.push(parent);
+                            }
+                        }
+                        if parent == root {
+                            root_children += 1;
+                        }
+                    }
+                }
+            }
+
+            // The root is only an articulation point if the DFS had to leave it more than once.
+            if root_children > 1 {
+                articulation_points.push(root);
+            }
+        }
+
+        bridges.sort_unstable();
+        articulation_points.sort_unstable();
+        articulation_points.dedup();
+        components.sort_unstable();
+        Biconnectivity {
+            bridges,
+            articulation_points,
+            components,
+        }
+    }
+
+    // Returns the neighbours of every vertex in the undirected version of the graph, sorted and
+    // without duplicates or self loops.
+    fn undirected_neighbours(&self) -> HashMap<i32, Vec<i32>> {
+        let mut neighbours: HashMap<i32, Vec<i32>> = self
+            .adjacencies
+            .keys()
+            .map(|&vertex| (vertex, vec![]))
+            .collect();
+        for (&tail, heads) in &self.adjacencies {
+            for &head in heads {
+                if head != tail {
+                    neighbours.entry(tail).or_default().push(head);
+                    neighbours.entry(head).or_default().push(tail);
+                }
+            }
+        }
+        for vertex_neighbours in neighbours.values_mut() {
+            vertex_neighbours.sort_unstable();
+            vertex_neighbours.dedup();
+        }
+        neighbours
+    }
+}
+
+fn sorted_edge([u, v]: [i32; 2]) -> [i32; 2] {
+    [u.min(v), u.max(v)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge_list::EdgeList;
+    use std::collections::HashSet;
+    use xorshift::XorShift;
+
+    fn graph(edges: Vec<[i32; 2]>) -> AdjacencyList {
+        AdjacencyList::from_edge_list(&EdgeList::new(edges))
+    }
+
+    // Counts the connected components of the undirected graph with the given vertices and
+    // edges.
+    fn count_components(vertices: &HashSet<i32>, edges: &[[i32; 2]]) -> usize {
+        let mut neighbours: HashMap<i32, Vec<i32>> = HashMap::new();
+        for &[u, v] in edges {
+            neighbours.entry(u).or_default().push(v);
+            neighbours.entry(v).or_default().push(u);
+        }
+        let mut seen: HashSet<i32> = HashSet::new();
+        let mut count = 0;
+        for &start in vertices {
+            if !seen.insert(start) {
+                continue;
+            }
+            count += 1;
+            let mut todo = vec![start];
+            while let Some(vertex) = todo.pop() {
+                for &next in neighbours.get(&vertex).into_iter().flatten() {
+                    if seen.insert(next) {
+                        todo.push(next);
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_disconnected_graph() {
+        // The triangle 1, 2, 3 with 6 hanging off 3, and 4 - 5 listed in both directions.
+        let graph =
+            AdjacencyList::from_edge_list(&EdgeList::parse_edge_list("test_file3.txt").unwrap());
+        assert_eq!(
+            graph.biconnectivity(),
+            Biconnectivity {
+                bridges: vec![[3, 6], [4, 5]],
+                articulation_points: vec![3],
+                components: vec![vec![[1, 2], [1, 3], [2, 3]], vec![[3, 6]], vec![[4, 5]]],
+            }
+        );
+    }
+
+    #[test]
+    fn test_bowtie() {
+        // Two triangles sharing vertex 3, with a tail 5 - 6 - 7. Self loops don't matter.
+        let graph = graph(vec![
+            [1, 2],
+            [2, 3],
+            [3, 1],
+            [3, 4],
+            [4, 5],
+            [5, 3],
+            [5, 6],
+            [6, 7],
+            [7, 7],
+        ]);
+        assert_eq!(
+            graph.biconnectivity(),
+            Biconnectivity {
+                bridges: vec![[5, 6], [6, 7]],
+                articulation_points: vec![3, 5, 6],
+                components: vec![
+                    vec![[1, 2], [1, 3], [2, 3]],
+                    vec![[3, 4], [3, 5], [4, 5]],
+                    vec![[5, 6]],
+                    vec![[6, 7]],
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_cycle_and_isolated_vertex() {
+        let graph = graph(vec![[1, 2], [2, 3], [3, 4], [4, 1], [5, 5]]);
+        assert_eq!(
+            graph.biconnectivity(),
+            Biconnectivity {
+                bridges: vec![],
+                articulation_points: vec![],
+                components: vec![vec![[1, 2], [1, 4], [2, 3], [3, 4]]],
+            }
+        );
+    }
+
+    #[test]
+    fn test_matches_brute_force_on_random_graphs() {
+        let mut rng = XorShift::new(0);
+        for _ in 0..50 {
+            let num_edges = rng.below(25);
+            let edges: Vec<[i32; 2]> = (0..num_edges)
+                .map(|_| [rng.below(12) as i32, rng.below(12) as i32])
+                .collect();
+            let graph = graph(edges);
+            let result = graph.biconnectivity();
+
+            let mut undirected: Vec<[i32; 2]> = graph
+                .adjacencies
+                .iter()
+                .flat_map(|(&tail, heads)| heads.iter().map(move |&head| sorted_edge([tail, head])))
+                .filter(|[u, v]| u != v)
+                .collect();
+            undirected.sort_unstable();
+            undirected.dedup();
+            let vertices: HashSet<i32> = graph.adjacencies.keys().copied().collect();
+            let baseline = count_components(&vertices, &undirected);
+
+            let bridges: Vec<[i32; 2]> = undirected
+                .iter()
+                .filter(|&edge| {
+                    let rest: Vec<[i32; 2]> =
+                        undirected.iter().filter(|&e| e != edge).copied().collect();
+                    count_components(&vertices, &rest) > baseline
+                })
+                .copied()
+                .collect();
+            assert_eq!(result.bridges, bridges);
+
+            let mut articulation_points: Vec<i32> = vertices
+                .iter()
+                .filter(|&&vertex| {
+                    let mut rest_vertices = vertices.clone();
+                    rest_vertices.remove(&vertex);
+                    let rest: Vec<[i32; 2]> = undirected
+                        .iter()
+                        .filter(|[u, v]| *u != vertex && *v != vertex)
+                        .copied()
+                        .collect();
+                    count_components(&rest_vertices, &rest) > baseline
+                })
+                .copied()
+                .collect();
+            articulation_points.sort_unstable();
+            assert_eq!(result.articulation_points, articulation_points);
+
+            // Every edge is in exactly one component, and the single edge components are
+            // exactly the bridges.
+            let mut component_edges: Vec<[i32; 2]> =
+                result.components.iter().flatten().copied().collect();
+            component_edges.sort_unstable();
+            assert_eq!(component_edges, undirected);
+            let single_edges: Vec<[i32; 2]> = result
+                .components
+                .iter()
+                .filter(|component| component.len() == 1)
+                .map(|component| component[0])
+                .collect();
+            assert_eq!(single_edges, result.bridges);
+        }
+    }
+
+    #[test]
+    fn test_long_path() {
+        // Deep enough that a recursive DFS would overflow the stack.
+        let n = 200_000;
+        let graph = graph((1..n).map(|vertex| [vertex, vertex + 1]).collect());
+        let result = graph.biconnectivity();
+        assert_eq!(result.bridges.len(), n as usize - 1);
+        assert_eq!(result.articulation_points, (2..n).collect::<Vec<i32>>());
+        assert_eq!(result.components.len(), n as usize - 1);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::adjacency_list::AdjacencyList;
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+
+// The result of a breadth-first search. Only vertices reachable from a source appear in either
+// map.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BfsTree {
+    // distances[v] is the least number of edges on a path from any source to v.
+    pub distances: HashMap<i32, usize>,
+    // parents[v] is the vertex before v on a shortest path to v. Sources have no parent.
+    pub parents: HashMap<i32, i32>,
+}
+
+impl BfsTree {
+    // Returns a shortest path from a source to target, starting with the source and ending
+    // with target, or None if target wasn't reached.
+    pub fn path_to(&self, target: i32) -> Option<Vec<i32>> {
+        if !self.distances.contains_key(&target) {
+            return None;
+        }
+        let mut path = vec![target];
+        let mut vertex = target;
+        while let Some(&parent) = self.parents.get(&vertex) {
+            path.push(parent);
+            vertex = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+impl AdjacencyList {
+    // Performs Breadth-First Search on self starting at source. If source isn't in the graph,
+    // the result is empty.
+    pub fn bfs(&self, source: i32) -> BfsTree {
+        self.multi_source_bfs(&[source])
+    }
+
+    // Performs Breadth-First Search starting from all of sources at once, so each distance is
+    // to the nearest source. Sources not in the graph are ignored.
+    pub fn multi_source_bfs(&self, sources: &[i32]) -> BfsTree {
+        self.bounded_bfs(sources, usize::MAX)
+    }
+
+    // Returns a shortest path from `from` to `to` counted in edges, starting with `from` and
+    // ending with `to`, or None if there is no such path.
+    pub fn shortest_path(&self, from: i32, to: i32) -> Option<Vec<i32>> {
+        self.bfs(from).path_to(to)
+    }
+
+    // Returns every vertex that can be reached from source using at most max_hops edges,
+    // including source itself.
+    pub fn reachable_within(&self, source: i32, max_hops: usize) -> HashSet<i32> {
+        self.bounded_bfs(&[source], max_hops)
+            .distances
+            .into_keys()
+            .collect()
+    }
+
+    // Breadth-First Search that doesn't look past vertices max_hops edges away from the
+    // sources.
+    fn bounded_bfs(&self, sources: &[i32], max_hops: usize) -> BfsTree {
+        let mut distances: HashMap<i32, usize> = HashMap::new();
+        let mut parents: HashMap<i32, i32> = HashMap::new();
+        let mut queue: VecDeque<i32> = VecDeque::new();
+
+        for &source in sources {
+            if self.adjacencies.contains_key(&source) && !distances.contains_key(&source) {
+                distances.insert(source, 0);
+                queue.push_back(source);
+            }
+        }
+
+        while let Some(vertex) = queue.pop_front() {
+            let distance = distances[&vertex];
+            if distance == max_hops {
+                continue;
+            }
+            for &head in &self.adjacencies[&vertex] {
+                if let Entry::Vacant(entry) = distances.entry(head) {
+                    entry.insert(distance + 1);
+                    parents.insert(head, vertex);
+                    queue.push_back(head);
+                }
+            }
+        }
+
+        BfsTree { distances, parents }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge_list::EdgeList;
+
+    // Two components: 1 -> 2 -> 3 -> 6 with a shortcut 1 -> 3, and the cycle 4 <-> 5.
+    fn disconnected_graph() -> AdjacencyList {
+        AdjacencyList::from_edge_list(&EdgeList::parse_edge_list("test_file3.txt").unwrap())
+    }
+
+    // The cycle 1 -> 2 -> 3 -> 1 with self loops on 1 and 2.
+    fn self_loop_graph() -> AdjacencyList {
+        AdjacencyList::from_edge_list(&EdgeList::parse_edge_list("test_file4.txt").unwrap())
+    }
+
+    #[test]
+    fn test_bfs() {
+        let tree =
+            AdjacencyList::from_edge_list(&EdgeList::parse_edge_list("test_file.txt").unwrap())
+                .bfs(1);
+        assert_eq!(tree.distances, HashMap::from([(1, 0), (2, 1), (3, 1)]));
+        assert_eq!(tree.parents, HashMap::from([(2, 1), (3, 1)]));
+    }
+
+    #[test]
+    fn test_bfs_disconnected() {
+        let tree = disconnected_graph().bfs(1);
+        assert_eq!(
+            tree.distances,
+            HashMap::from([(1, 0), (2, 1), (3, 1), (6, 2)])
+        );
+        assert_eq!(tree.parents, HashMap::from([(2, 1), (3, 1), (6, 3)]));
+        assert_eq!(tree.path_to(6), Some(vec![1, 3, 6]));
+        assert_eq!(tree.path_to(1), Some(vec![1]));
+        assert_eq!(tree.path_to(4), None);
+
+        assert!(disconnected_graph().bfs(7).distances.is_empty());
+    }
+
+    #[test]
+    fn test_bfs_self_loops() {
+        let tree = self_loop_graph().bfs(1);
+        assert_eq!(tree.distances, HashMap::from([(1, 0), (2, 1), (3, 2)]));
+        assert_eq!(tree.parents, HashMap::from([(2, 1), (3, 2)]));
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let graph = disconnected_graph();
+        assert_eq!(graph.shortest_path(1, 6), Some(vec![1, 3, 6]));
+        assert_eq!(graph.shortest_path(4, 5), Some(vec![4, 5]));
+        assert_eq!(graph.shortest_path(6, 1), None);
+        assert_eq!(graph.shortest_path(1, 4), None);
+
+        let graph = self_loop_graph();
+        assert_eq!(graph.shortest_path(2, 2), Some(vec![2]));
+        assert_eq!(graph.shortest_path(3, 2), Some(vec![3, 1, 2]));
+    }
+
+    #[test]
+    fn test_multi_source_bfs() {
+        let tree = disconnected_graph().multi_source_bfs(&[2, 4, 9]);
+        assert_eq!(
+            tree.distances,
+            HashMap::from([(2, 0), (3, 1), (6, 2), (4, 0), (5, 1)])
+        );
+        assert_eq!(tree.path_to(6), Some(vec![2, 3, 6]));
+        assert_eq!(tree.path_to(5), Some(vec![4, 5]));
+        assert_eq!(tree.path_to(1), None);
+
+        // Repeating a source doesn't change anything.
+        assert_eq!(
+            disconnected_graph().multi_source_bfs(&[2, 2]),
+            disconnected_graph().bfs(2)
+        );
+    }
+
+    #[test]
+    fn test_reachable_within() {
+        let graph = disconnected_graph();
+        assert_eq!(graph.reachable_within(1, 0), HashSet::from([1]));
+        assert_eq!(graph.reachable_within(1, 1), HashSet::from([1, 2, 3]));
+        assert_eq!(graph.reachable_within(1, 2), HashSet::from([1, 2, 3, 6]));
+        assert_eq!(graph.reachable_within(1, 100), HashSet::from([1, 2, 3, 6]));
+        assert_eq!(graph.reachable_within(7, 100), HashSet::new());
+
+        let graph = self_loop_graph();
+        assert_eq!(graph.reachable_within(3, 1), HashSet::from([3, 1]));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::edge_list::EdgeList;
+use crate::graph::Graph;
+
+// A graph in compressed sparse row form. The outgoing edges of every vertex are stored next to
+// each other in one contiguous array, so building the graph only takes a handful of
+// allocations and looking up the edges of a vertex doesn't need any hashing.
+#[derive(Debug, PartialEq)]
+pub struct CsrGraph {
+    // The vertices of the graph in increasing order. The vertex vertices[i] has index i.
+    vertices: Vec<i32>,
+    // Maps a vertex to its index without searching when the vertices are packed closely
+    // together, as they are in the assignment inputs.
+    lookup: VertexLookup,
+    // The heads of the edges leaving the vertex with index i are
+    // targets[offsets[i]..offsets[i + 1]], in the order they appeared in the input.
+    offsets: Vec<usize>,
+    targets: Vec<i32>,
+}
+
+impl CsrGraph {
+    // Takes an Edge List and returns a CsrGraph.
+    pub fn from_edge_list(edge_list: &EdgeList) -> CsrGraph {
+        let edges = edge_list.edges.iter().map(|&[tail, head]| (tail, head));
+        Self::from_edges(vertices_of(edge_list), edges)
+    }
+
+    // Takes an Edge List and returns the CsrGraph of the graph with every edge reversed.
+    pub fn from_reversed_edge_list(edge_list: &EdgeList) -> CsrGraph {
+        let edges = edge_list.edges.iter().map(|&[tail, head]| (head, tail));
+        Self::from_edges(vertices_of(edge_list), edges)
+    }
+
+    // Returns the index of vertex, or None if vertex isn't in the graph.
+    fn index_of(&self, vertex: i32) -> Option<usize> {
+        self.lookup.index_of(&self.vertices, vertex)
+    }
+
+    // Builds the graph in two passes over edges. The first pass counts the edges leaving each
+    // vertex, which gives the offsets, and the second copies each head into its slot. vertices
+    // must be sorted, without duplicates, and contain every endpoint of edges.
+    fn from_edges(vertices: Vec<i32>, edges: impl Iterator<Item = (i32, i32)> + Clone) -> CsrGraph {
+        let lookup = VertexLookup::new(&vertices);
+        let index_of = |vertex: i32| {
+            lookup
+                .index_of(&vertices, vertex)
+                .expect("Every endpoint should be a vertex")
+        };
+
+        let mut offsets = vec![0; vertices.len() + 1];
+        for (tail, _) in edges.clone() {
+            offsets[index_of(tail) + 1] += 1;
+        }
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+
+        // next_slot[i] is where the next head leaving the vertex with index i goes.
+        let mut next_slot = offsets[..vertices.len()].to_vec();
+        let mut targets = vec![0; offsets[vertices.len()]];
+        for (tail, head) in edges {
+            let slot = &mut next_slot[index_of(tail)];
+            targets[*slot] = head;
+            *slot += 1;
+        }
+
+        CsrGraph {
+            vertices,
+            lookup,
+            offsets,
+            targets,
+        }
+    }
+}
+
+impl Graph for CsrGraph {
+    fn num_nodes(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn sorted_vertices(&self) -> Vec<i32> {
+        self.vertices.clone()
+    }
+
+    fn successors(&self, vertex: i32) -> Option<&[i32]> {
+        let index = self.index_of(vertex)?;
+        Some(&self.targets[self.offsets[index]..self.offsets[index + 1]])
+    }
+
+    fn reversed(&self) -> CsrGraph {
+        let edges = self.vertices.iter().enumerate().flat_map(|(index, &tail)| {
+            self.targets[self.offsets[index]..self.offsets[index + 1]]
+                .iter()
+                .map(move |&head| (head, tail))
+        });
+        Self::from_edges(self.vertices.clone(), edges)
+    }
+}
+
+// How a CsrGraph finds the index of a vertex.
+#[derive(Debug, PartialEq)]
+pub(crate) enum VertexLookup {
+    // indices[vertex - first] is the index of vertex, or NO_INDEX if there is no such vertex.
+    Table { first: i32, indices: Vec<u32> },
+    // The vertices are too spread out for a table, so binary search them instead.
+    Search,
+}
+
+const NO_INDEX: u32 = u32::MAX;
+
+impl VertexLookup {
+    // Uses a table as long as it would be at most a few times larger than the vertex list.
+    pub(crate) fn new(vertices: &[i32]) -> VertexLookup {
+        let (Some(&first), Some(&last)) = (vertices.first(), vertices.last()) else {
+            return VertexLookup::Search;
+        };
+        let span = (last as i64 - first as i64 + 1) as usize;
+        if span > 4 * vertices.len() || vertices.len() >= NO_INDEX as usize {
+            return VertexLookup::Search;
+        }
+        let mut indices = vec![NO_INDEX; span];
+        for (index, &vertex) in vertices.iter().enumerate() {
+            indices[(vertex - first) as usize] = index as u32;
+        }
+        VertexLookup::Table { first, indices }
+    }
+
+    pub(crate) fn index_of(&self, vertices: &[i32], vertex: i32) -> Option<usize> {
+        match self {
+            VertexLookup::Table { first, indices } => {
+                let offset = usize::try_from(vertex as i64 - *first as i64).ok()?;
+                match indices.get(offset) {
+                    Some(&index) if index != NO_INDEX => Some(index as usize),
+                    _ => None,
+                }
+            }
+            VertexLookup::Search => vertices.binary_search(&vertex).ok(),
+        }
+    }
+}
+
+// Returns every endpoint of the edges in edge_list, sorted and without duplicates.
+fn vertices_of(edge_list: &EdgeList) -> Vec<i32> {
+    let mut vertices: Vec<i32> = edge_list.edges.iter().flatten().copied().collect();
+    vertices.sort_unstable();
+    vertices.dedup();
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjacency_list::AdjacencyList;
+    use std::collections::HashSet;
+
+    fn setup_edge_list() -> EdgeList {
+        EdgeList::new(vec![[3, 1], [1, 2], [1, 3], [2, 1], [5, 5]])
+    }
+
+    #[test]
+    fn test_from_edge_list() {
+        let graph = CsrGraph::from_edge_list(&setup_edge_list());
+        assert_eq!(
+            graph,
+            CsrGraph {
+                vertices: vec![1, 2, 3, 5],
+                lookup: VertexLookup::Table {
+                    first: 1,
+                    indices: vec![0, 1, 2, NO_INDEX, 3]
+                },
+                offsets: vec![0, 2, 3, 4, 5],
+                targets: vec![2, 3, 1, 1, 5],
+            }
+        );
+        assert_eq!(graph.num_nodes(), 4);
+        assert_eq!(graph.successors(1), Some(&[2, 3][..]));
+        assert_eq!(graph.successors(4), None);
+        assert_eq!(graph.successors(0), None);
+        assert_eq!(graph.successors(6), None);
+    }
+
+    #[test]
+    fn test_spread_out_vertices() {
+        let edge_list = EdgeList::new(vec![[i32::MIN, 0], [0, i32::MAX], [i32::MAX, i32::MIN]]);
+        let graph = CsrGraph::from_edge_list(&edge_list);
+        assert_eq!(graph.lookup, VertexLookup::Search);
+        assert_eq!(graph.successors(i32::MIN), Some(&[0][..]));
+        assert_eq!(graph.successors(i32::MAX), Some(&[i32::MIN][..]));
+        assert_eq!(graph.successors(1), None);
+    }
+
+    #[test]
+    fn test_reversed() {
+        let edge_list = setup_edge_list();
+        // Heads keep the order their edges appeared in the Edge List.
+        let reversed = CsrGraph::from_reversed_edge_list(&edge_list);
+        assert_eq!(reversed.successors(1), Some(&[3, 2][..]));
+        assert_eq!(reversed.successors(3), Some(&[1][..]));
+        assert_eq!(reversed.successors(5), Some(&[5][..]));
+
+        // Reversing a CsrGraph goes through the tails in increasing order instead.
+        let reversed = CsrGraph::from_edge_list(&edge_list).reversed();
+        assert_eq!(reversed.sorted_vertices(), vec![1, 2, 3, 5]);
+        assert_eq!(reversed.successors(1), Some(&[2, 3][..]));
+        assert_eq!(reversed.successors(2), Some(&[1][..]));
+        assert_eq!(reversed.successors(3), Some(&[1][..]));
+    }
+
+    #[test]
+    fn test_matches_adjacency_list() {
+        let edge_list = setup_edge_list();
+        let csr = CsrGraph::from_edge_list(&edge_list);
+        let adjacency_list = AdjacencyList::from_edge_list(&edge_list);
+        assert_eq!(csr.sorted_vertices(), adjacency_list.sorted_vertices());
+        for vertex in csr.sorted_vertices() {
+            assert_eq!(csr.successors(vertex), adjacency_list.successors(vertex));
+            assert_eq!(
+                csr.dfs(&vertex, &mut HashSet::new()),
+                adjacency_list.dfs(&vertex, &mut HashSet::new())
+            );
+        }
+    }
+}
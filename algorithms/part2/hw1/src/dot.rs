@@ -0,0 +1,74 @@
+use crate::adjacency_list::AdjacencyList;
+use crate::graph::Graph;
+use crate::strongly_connected::{StronglyConnected, Tarjan};
+use std::{collections::HashMap, fmt::Write};
+
+// Number of colours in the Graphviz colour scheme used for components.
+const NUM_COLOURS: usize = 12;
+
+impl AdjacencyList {
+    // Returns the graph in Graphviz DOT format with every vertex coloured by its strongly
+    // connected component. Vertices and edges are written in sorted order and components are
+    // numbered by their smallest vertex, so the same graph always gives the same output.
+    pub fn to_dot(&self) -> String {
+        let mut components = Tarjan.scc(self);
+        components
+            .iter_mut()
+            .for_each(|component| component.sort_unstable());
+        components.sort_unstable_by_key(|component| component[0]);
+        let component_of: HashMap<i32, usize> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(id, vertices)| vertices.iter().map(move |&vertex| (vertex, id)))
+            .collect();
+
+        let mut dot = String::from("digraph {\n");
+        dot.push_str("    node [style=filled, colorscheme=set312];\n");
+        for vertex in self.sorted_vertices() {
+            let id = component_of[&vertex];
+            writeln!(
+                dot,
+                "    {} [fillcolor={}, tooltip=\"scc {}\"];",
+                vertex,
+                id % NUM_COLOURS + 1,
+                id
+            )
+            .unwrap();
+        }
+        for tail in self.sorted_vertices() {
+            let mut heads = self.adjacencies[&tail].clone();
+            heads.sort_unstable();
+            for head in heads {
+                writeln!(dot, "    {} -> {};", tail, head).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge_list::EdgeList;
+    use std::fs;
+
+    #[test]
+    fn test_to_dot() {
+        let graph =
+            AdjacencyList::from_edge_list(&EdgeList::parse_edge_list("test_file3.txt").unwrap());
+        assert_eq!(
+            graph.to_dot(),
+            fs::read_to_string("test_file3.dot").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_to_dot_is_deterministic() {
+        let edge_list = EdgeList::parse_edge_list("test_file4.txt").unwrap();
+        let dot = AdjacencyList::from_edge_list(&edge_list).to_dot();
+        for _ in 0..10 {
+            assert_eq!(AdjacencyList::from_edge_list(&edge_list).to_dot(), dot);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::csr_graph::VertexLookup;
+use std::collections::HashSet;
+
+// Operations shared by the directed graph representations in this crate, so that algorithms
+// like dfs and scc can run on any of them.
+pub trait Graph {
+    // Returns the number of nodes (vertices) in the graph.
+    fn num_nodes(&self) -> usize;
+
+    // Returns the vertices of the graph in increasing order.
+    fn sorted_vertices(&self) -> Vec<i32>;
+
+    // Returns the heads of the edges leaving vertex, or None if vertex isn't in the graph.
+    fn successors(&self, vertex: i32) -> Option<&[i32]>;
+
+    // Returns the graph with every edge reversed.
+    fn reversed(&self) -> Self
+    where
+        Self: Sized;
+
+    // Performs Depth-First Search on self. If the first node has already been visited or
+    // doesn't exist in the graph, returns None; else, returns Some vector of
+    // all visited nodes ordered by finishing time.
+    fn dfs(&self, start: &i32, visited: &mut HashSet<i32>) -> Option<Vec<i32>> {
+        dfs_marking(self, *start, |vertex| visited.insert(vertex))
+    }
+}
+
+// The search behind Graph::dfs. mark(vertex) records that vertex has been visited and returns
+// whether it hadn't been already, so callers can keep track of that however suits them.
+pub(crate) fn dfs_marking<G: Graph + ?Sized>(
+    graph: &G,
+    start: i32,
+    mut mark: impl FnMut(i32) -> bool,
+) -> Option<Vec<i32>> {
+    // If start is already visited, mark returns false and we return None.
+    if !mark(start) {
+        return None;
+    };
+
+    // If start is not contained in the graph, then return None; else, initialize todo list
+    // with starting vertex and its outgoing edges.
+    let mut todo = vec![(start, graph.successors(start)?)];
+    let mut finishing_times: Vec<i32> = vec![];
+
+    while let Some((current_vertex, next_vertices)) = todo.pop() {
+        match next_vertices {
+            // If there are no outgoing edges to process, this vertex is finished and so
+            // we add it to finishing_times.
+            [] => finishing_times.push(current_vertex),
+            // If there is at least one outgoing edge to process, we must check whether it
+            // has been visited or not. First we push the current vertex and its remaining
+            // outgoing edges back onto the todo list. Then we check if next_vertex has been
+            // visited. If it has, we do nothing; else, we push it and its outgoing edges to
+            // the todo list.
+            [next_vertex, rest_vertices @ ..] => {
+                todo.push((current_vertex, rest_vertices));
+                if mark(*next_vertex) {
+                    if let Some(next_outgoing) = graph.successors(*next_vertex) {
+                        todo.push((*next_vertex, next_outgoing))
+                    }
+                }
+            }
+        }
+    }
+    Some(finishing_times)
+}
+
+// Which vertices of a graph have been visited, as a flag per vertex indexed by its position in
+// sorted_vertices, looked up the way CsrGraph does, so marking a vertex doesn't hash it.
+pub(crate) struct VisitedVertices {
+    vertices: Vec<i32>,
+    lookup: VertexLookup,
+    visited: Vec<bool>,
+}
+
+impl VisitedVertices {
+    // Creates a set with none of the vertices of graph visited.
+    pub(crate) fn new<G: Graph>(graph: &G) -> VisitedVertices {
+        let vertices = graph.sorted_vertices();
+        let lookup = VertexLookup::new(&vertices);
+        let visited = vec![false; vertices.len()];
+        VisitedVertices {
+            vertices,
+            lookup,
+            visited,
+        }
+    }
+
+    // Marks vertex as visited and returns whether it hadn't been already, like HashSet::insert.
+    // Vertices that aren't in the graph have no successors to visit, so they count as visited.
+    pub(crate) fn insert(&mut self, vertex: i32) -> bool {
+        match self.lookup.index_of(&self.vertices, vertex) {
+            Some(index) => !std::mem::replace(&mut self.visited[index], true),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use crate::adjacency_list::AdjacencyList;
+use crate::graph::Graph;
+use crate::strongly_connected::{StronglyConnected, Tarjan};
+use crate::union_find::UnionFind;
+use std::{cell::RefCell, collections::HashMap};
+
+// Position of a vertex in the vectors of IncrementalScc.
+type Index = usize;
+
+// Keeps track of the strongly connected components of a graph while edges are added to it.
+// Components are stored in a union-find structure, so same_component only costs two finds.
+// Adding an edge only merges components, and only searches the part of the condensation graph
+// reachable from the new edge's head instead of recomputing every component from scratch.
+#[derive(Debug)]
+pub struct IncrementalScc {
+    indices: HashMap<i32, Index>,
+    vertices: Vec<i32>,
+    // Union-find with path compression, like the one used for clustering. RefCell lets
+    // same_component compress paths without needing &mut self.
+    components: RefCell<UnionFind>,
+    // out_edges[root] holds vertices that the component with that root has an edge to. Entries
+    // can go stale when components merge, so they are always looked up through find, and
+    // entries that lead back into their own component are dropped when components merge.
+    out_edges: Vec<Vec<Index>>,
+}
+
+impl IncrementalScc {
+    // Creates an IncrementalScc starting from the components of graph.
+    pub fn new(graph: &AdjacencyList) -> IncrementalScc {
+        let mut scc = IncrementalScc {
+            indices: HashMap::with_capacity(graph.num_nodes()),
+            vertices: Vec::with_capacity(graph.num_nodes()),
+            components: RefCell::new(UnionFind::with_capacity(graph.num_nodes())),
+            out_edges: Vec::with_capacity(graph.num_nodes()),
+        };
+        for vertex in graph.sorted_vertices() {
+            scc.index_of_or_insert(vertex);
+        }
+        for component in Tarjan.scc(graph) {
+            let first = scc.indices[&component[0]];
+            for vertex in &component[1..] {
+                scc.union(first, scc.indices[vertex]);
+            }
+        }
+        for (tail, heads) in &graph.adjacencies {
+            let root = scc.find(scc.indices[tail]);
+            for head in heads {
+                let head_root = scc.find(scc.indices[head]);
+                if head_root != root {
+                    scc.out_edges[root].push(head_root);
+                }
+            }
+        }
+        for heads in &mut scc.out_edges {
+            heads.sort_unstable();
+            heads.dedup();
+        }
+        scc
+    }
+
+    // Returns the number of strongly connected components.
+    pub fn num_components(&self) -> usize {
+        self.components.borrow().count()
+    }
+
+    // Returns true if u and v are in the same strongly connected component. Vertices that
+    // aren't in the graph are only in the same component as themselves.
+    pub fn same_component(&self, u: i32, v: i32) -> bool {
+        match (self.indices.get(&u), self.indices.get(&v)) {
+            (Some(&u_index), Some(&v_index)) => self.find(u_index) == self.find(v_index),
+            _ => u == v,
+        }
+    }
+
+    // Returns the strongly connected components, each sorted, in order of their smallest
+    // vertex.
+    pub fn components(&self) -> Vec<Vec<i32>> {
+        let mut components: HashMap<Index, Vec<i32>> = HashMap::new();
+        for (index, &vertex) in self.vertices.iter().enumerate() {
+            components.entry(self.find(index)).or_default().push(vertex);
+        }
+        let mut components: Vec<Vec<i32>> = components.into_values().collect();
+        components.iter_mut().for_each(|x| x.sort_unstable());
+        components.sort_unstable();
+        components
+    }
+
+    // Adds the edge tail -> head, adding either vertex if it's new. If the edge closes a cycle
+    // through several components, they are merged into one. Returns true if any components
+    // were merged.
+    pub fn insert_edge(&mut self, tail: i32, head: i32) -> bool {
+        let (tail, head) = (self.index_of_or_insert(tail), self.index_of_or_insert(head));
+        let (tail_root, head_root) = (self.find(tail), self.find(head));
+        if tail_root == head_root {
+            return false;
+        }
+
+        // The new edge puts every component on a path from head back to tail on a cycle.
+        let on_cycle = self.components_between(head_root, tail_root);
+        if on_cycle.is_empty() {
+            self.out_edges[tail_root].push(head_root);
+            return false;
+        }
+
+        let mut heads: Vec<Index> = vec![];
+        for &root in &on_cycle {
+            heads.append(&mut self.out_edges[root]);
+        }
+        for &root in &on_cycle[1..] {
+            self.union(on_cycle[0], root);
+        }
+        let merged_root = self.find(on_cycle[0]);
+        let mut heads: Vec<Index> = heads
+            .into_iter()
+            .map(|head| self.find(head))
+            .filter(|&head_root| head_root != merged_root)
+            .collect();
+        heads.sort_unstable();
+        heads.dedup();
+        self.out_edges[merged_root] = heads;
+        true
+    }
+
+    // Returns the roots of every component that lies on a path from the component rooted at
+    // from to the one rooted at to, including both of them, or nothing if there is no such
+    // path. The condensation graph is a DAG, so a component reaches to exactly when one of
+    // its successors does, which we can work out when the DFS finishes it.
+    fn components_between(&self, from: Index, to: Index) -> Vec<Index> {
+        // reaches[root] is set once the DFS has finished root.
+        let mut reaches: HashMap<Index, bool> = HashMap::new();
+        // Each entry holds a root, how many of its out edges we've followed, and whether any of
+        // its finished successors reach to.
+        let mut todo: Vec<(Index, usize, bool)> = vec![(from, 0, from == to)];
+
+        while let Some((root, next_edge, reaches_to)) = todo.last_mut() {
+            let root = *root;
+            // We never need to look past to, since nothing after it can lead back to it.
+            let next_head = match self.out_edges[root].get(*next_edge) {
+                Some(&head) if root != to => head,
+                _ => {
+                    let reaches_to = *reaches_to;
+                    todo.pop();
+                    reaches.insert(root, reaches_to);
+                    if let Some((_, _, parent_reaches_to)) = todo.last_mut() {
+                        *parent_reaches_to |= reaches_to;
+                    }
+                    continue;
+                }
+            };
+            *next_edge += 1;
+            let head_root = self.find(next_head);
+            match reaches.get(&head_root) {
+                Some(&head_reaches_to) => *reaches_to |= head_reaches_to,
+                None => todo.push((head_root, 0, head_root == to)),
+            }
+        }
+
+        reaches
+            .into_iter()
+            .filter(|&(_, reaches_to)| reaches_to)
+            .map(|(root, _)| root)
+            .collect()
+    }
+
+    // Returns the index of vertex, adding it as a component of its own if it's new.
+    fn index_of_or_insert(&mut self, vertex: i32) -> Index {
+        if let Some(&index) = self.indices.get(&vertex) {
+            return index;
+        }
+        let index = self.vertices.len();
+        self.indices.insert(vertex, index);
+        self.vertices.push(vertex);
+        self.components.get_mut().push();
+        self.out_edges.push(vec![]);
+        index
+    }
+
+    // Finds the root of the component containing index.
+    fn find(&self, index: Index) -> Index {
+        self.components.borrow_mut().find(index)
+    }
+
+    // Merges the components containing u and v.
+    fn union(&mut self, u: Index, v: Index) {
+        self.components.get_mut().union(u, v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge_list::EdgeList;
+    use xorshift::XorShift;
+
+    fn random_edges(seed: u64, num_vertices: usize, num_edges: usize) -> Vec<[i32; 2]> {
+        let mut rng = XorShift::new(seed);
+        let mut vertex = || rng.below(num_vertices) as i32;
+        (0..num_edges).map(|_| [vertex(), vertex()]).collect()
+    }
+
+    fn batch_components(edges: &[[i32; 2]]) -> Vec<Vec<i32>> {
+        let mut components = EdgeList::new(edges.to_vec()).scc();
+        components.iter_mut().for_each(|x| x.sort_unstable());
+        components.sort_unstable();
+        components
+    }
+
+    #[test]
+    fn test_new() {
+        let graph =
+            AdjacencyList::from_edge_list(&EdgeList::parse_edge_list("test_file2.txt").unwrap());
+        let scc = IncrementalScc::new(&graph);
+        assert_eq!(scc.components(), vec![vec![1, 2], vec![3]]);
+        assert_eq!(scc.num_components(), 2);
+        assert!(scc.same_component(1, 2));
+        assert!(!scc.same_component(1, 3));
+        assert!(!scc.same_component(1, 4));
+        assert!(scc.same_component(4, 4));
+    }
+
+    #[test]
+    fn test_insert_edge() {
+        // 1 -> 2 -> 3 -> 4 with a side branch 2 -> 5.
+        let graph =
+            AdjacencyList::from_edge_list(&EdgeList::new(vec![[1, 2], [2, 3], [3, 4], [2, 5]]));
+        let mut scc = IncrementalScc::new(&graph);
+        assert_eq!(scc.num_components(), 5);
+
+        assert!(!scc.insert_edge(1, 4));
+        assert!(scc.insert_edge(4, 2));
+        assert_eq!(scc.components(), vec![vec![1], vec![2, 3, 4], vec![5]]);
+
+        // 6 is a new vertex.
+        assert!(!scc.insert_edge(5, 6));
+        assert!(scc.insert_edge(6, 1));
+        assert_eq!(scc.components(), vec![vec![1, 2, 3, 4, 5, 6]]);
+        assert!(scc.same_component(6, 3));
+        assert!(!scc.insert_edge(3, 3));
+    }
+
+    #[test]
+    fn test_matches_batch_on_random_insertions() {
+        for seed in 1..=30 {
+            let edges = random_edges(seed, 25, 50);
+            let (initial, inserted) = edges.split_at(10);
+            let mut scc = IncrementalScc::new(&AdjacencyList::from_edge_list(&EdgeList::new(
+                initial.to_vec(),
+            )));
+            for (i, &[tail, head]) in inserted.iter().enumerate() {
+                scc.insert_edge(tail, head);
+                let expected = batch_components(&edges[..initial.len() + i + 1]);
+                assert_eq!(scc.components(), expected);
+                assert_eq!(scc.num_components(), expected.len());
+                for component in &expected {
+                    assert!(scc.same_component(component[0], *component.last().unwrap()));
+                }
+            }
+        }
+    }
+}
@@ -1,29 +1,170 @@
+pub mod biconnected;
+pub mod breadth_first;
+pub mod csr_graph;
+pub mod dot;
+pub mod graph;
+pub mod incremental_scc;
+pub mod min_cut;
+pub mod strongly_connected;
+pub mod topological_sort;
+mod union_find;
+
 pub mod edge_list {
-    use std::{fs, collections::HashSet};
-    use super::adjacency_list::AdjacencyList;
+    use std::{
+        collections::HashMap,
+        fmt,
+        fs::File,
+        io::{self, BufRead, BufReader},
+        str::FromStr,
+    };
+    use super::csr_graph::CsrGraph;
+    use super::strongly_connected::kosaraju;
 
     #[derive(Debug, PartialEq)]
     pub struct EdgeList {
         pub edges: Vec<[i32; 2]>,
+        // weights[i] is the weight of edges[i], if the edges have weights at all.
+        pub weights: Option<Vec<i64>>,
+    }
+
+    // The strongly connected components of a graph along with the condensation graph, i.e.
+    // the DAG obtained by contracting each component to a single vertex.
+    #[derive(Debug, PartialEq)]
+    pub struct Condensation {
+        // components[id] holds the vertices of component id. Components are numbered in
+        // topological order, so every edge of the DAG goes from a lower id to a higher id.
+        pub components: Vec<Vec<i32>>,
+        // Maps each vertex to the id of the component containing it.
+        pub component_of: HashMap<i32, usize>,
+        // dag[id] holds the ids of the components that component id has an edge to, sorted
+        // and without duplicates.
+        pub dag: Vec<Vec<usize>>,
+    }
+
+    // Everything that can go wrong while parsing an edge list. Lines are numbered from 1, and
+    // columns count whitespace-separated fields on a line, also from 1.
+    #[derive(Debug)]
+    pub enum ParseError {
+        // The input couldn't be read.
+        Io(io::Error),
+        // A line didn't have two vertices and an optional weight.
+        WrongColumnCount { line: usize, found: usize },
+        // A column couldn't be parsed as a number.
+        InvalidNumber { line: usize, column: usize, value: String },
+        // Some lines had a weight column and others didn't.
+        InconsistentWeights { line: usize },
+    }
+
+    impl fmt::Display for ParseError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ParseError::Io(error) => write!(f, "couldn't read edge list: {}", error),
+                ParseError::WrongColumnCount { line, found } => write!(
+                    f,
+                    "line {}: expected two vertices and an optional weight, found {} columns",
+                    line, found
+                ),
+                ParseError::InvalidNumber { line, column, value } => {
+                    write!(f, "line {}, column {}: '{}' isn't a number", line, column, value)
+                }
+                ParseError::InconsistentWeights { line } => write!(
+                    f,
+                    "line {}: either every edge needs a weight or none of them can have one",
+                    line
+                ),
+            }
+        }
+    }
+
+    impl std::error::Error for ParseError {}
+
+    impl From<io::Error> for ParseError {
+        fn from(error: io::Error) -> Self {
+            ParseError::Io(error)
+        }
+    }
+
+    // Parses columns[column] as a number, reporting where it was if it isn't one.
+    pub(crate) fn parse_column<T: FromStr>(
+        columns: &[&str],
+        column: usize,
+        line: usize,
+    ) -> Result<T, ParseError> {
+        columns[column].parse().map_err(|_| ParseError::InvalidNumber {
+            line,
+            column: column + 1,
+            value: columns[column].to_string(),
+        })
+    }
+
+    impl FromStr for EdgeList {
+        type Err = ParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            EdgeList::from_reader(s.as_bytes())
+        }
     }
 
     impl EdgeList {
-        // Reads in a file containing an edge list and returns an EdgeList.
-        pub fn parse_edge_list(filepath: &str) -> EdgeList {
-            let f = fs::read_to_string(filepath).expect("Couldn't open file.");
-
-            let edges: Vec<[i32; 2]> = f
-                .lines()
-                .map(|l| {
-                    l.trim_end()
-                        .split(" ")
-                        .map(|s| s.parse().expect("Expected an i32"))
-                        .collect::<Vec<i32>>()
-                        .try_into()
-                        .expect("Expected two node names per line separated by spaces")
-                })
-                .collect();
-            EdgeList { edges }
+        // Returns an unweighted EdgeList with the given edges.
+        pub fn new(edges: Vec<[i32; 2]>) -> EdgeList {
+            EdgeList { edges, weights: None }
+        }
+
+        // Reads in a file containing an edge list and returns an EdgeList. The file is read a
+        // line at a time, so it never has to fit in memory as a whole.
+        pub fn parse_edge_list(filepath: &str) -> Result<EdgeList, ParseError> {
+            EdgeList::from_reader(BufReader::new(File::open(filepath)?))
+        }
+
+        // Reads an edge list from reader. Each line holds a tail vertex, a head vertex and
+        // optionally an integer weight, separated by any whitespace. Everything after a '#' is
+        // a comment, and blank lines are skipped. Either every edge has a weight or none do.
+        pub fn from_reader<R: BufRead>(mut reader: R) -> Result<EdgeList, ParseError> {
+            let mut edges: Vec<[i32; 2]> = vec![];
+            let mut weights: Vec<i64> = vec![];
+            let mut weighted: Option<bool> = None;
+            let mut line = String::new();
+            let mut line_number = 0;
+
+            loop {
+                line.clear();
+                if reader.read_line(&mut line)? == 0 {
+                    break;
+                }
+                line_number += 1;
+
+                let data = match line.split_once('#') {
+                    Some((data, _comment)) => data,
+                    None => &line,
+                };
+                let columns: Vec<&str> = data.split_whitespace().collect();
+                if columns.is_empty() {
+                    continue;
+                }
+
+                match columns.len() {
+                    2 | 3 => edges.push([
+                        parse_column(&columns, 0, line_number)?,
+                        parse_column(&columns, 1, line_number)?,
+                    ]),
+                    found => {
+                        return Err(ParseError::WrongColumnCount { line: line_number, found })
+                    }
+                }
+                let has_weight = columns.len() == 3;
+                if *weighted.get_or_insert(has_weight) != has_weight {
+                    return Err(ParseError::InconsistentWeights { line: line_number });
+                }
+                if has_weight {
+                    weights.push(parse_column(&columns, 2, line_number)?);
+                }
+            }
+
+            Ok(EdgeList {
+                edges,
+                weights: weighted.unwrap_or(false).then_some(weights),
+            })
         }
 
         // Reverses the edges in the EdgeList.
@@ -39,41 +180,49 @@ pub mod edge_list {
         // Returns the strongly connected components of the graph.
         // Each element in the first vector is a vector of vertices,
         // where each vector of vertices is a strongly connected
-        // component.
-        pub fn scc(&mut self) -> Vec<Vec<i32>> {
-            let graph = AdjacencyList::from_edge_list(self);
-            self.reverse();
-            let reversed_graph = AdjacencyList::from_edge_list(self);
-            self.reverse();
-            let mut visited: HashSet<i32> = HashSet::new();
-            let mut finishing_times: Vec<i32> = vec![];
-
-            for vertex in reversed_graph.adjacencies.keys() {
-                if !visited.contains(vertex) {
-                    if let Some(mut vertices) = 
-                        reversed_graph.dfs(vertex, &mut visited) {
-                        finishing_times.append(&mut vertices);
-                    }
-                }
-            }
+        // component. Components are ordered so that sink components
+        // come first.
+        pub fn scc(&self) -> Vec<Vec<i32>> {
+            let graph = CsrGraph::from_edge_list(self);
+            let reversed_graph = CsrGraph::from_reversed_edge_list(self);
+            kosaraju(&graph, &reversed_graph)
+        }
+
+        // Returns the strongly connected components of the graph together with the
+        // condensation graph between them. Unlike reverse, this doesn't modify self, so the
+        // EdgeList can be shared freely.
+        pub fn condensation(&self) -> Condensation {
+            // scc returns sink components first, so reversing puts them in topological order.
+            let mut components = self.scc();
+            components.reverse();
 
-            visited = HashSet::new();
-            let mut result: Vec<Vec<i32>> = vec![];
+            let component_of: HashMap<i32, usize> = components
+                .iter()
+                .enumerate()
+                .flat_map(|(id, vertices)| vertices.iter().map(move |&vertex| (vertex, id)))
+                .collect();
 
-            for vertex in finishing_times.iter().rev() {
-                if let Some(visited_vertices) = graph.dfs(vertex, &mut visited) {
-                    result.push(visited_vertices);
+            let mut dag: Vec<Vec<usize>> = vec![vec![]; components.len()];
+            for [tail, head] in &self.edges {
+                let (from, to) = (component_of[tail], component_of[head]);
+                if from != to {
+                    dag[from].push(to);
                 }
             }
+            for heads in &mut dag {
+                heads.sort_unstable();
+                heads.dedup();
+            }
 
-            result
+            Condensation { components, component_of, dag }
         }
     }
 }
 
 pub mod adjacency_list {
     use super::edge_list::*;
-    use std::collections::{HashMap, HashSet};
+    use super::graph::Graph;
+    use std::collections::HashMap;
 
     #[derive(Debug, PartialEq)]
     pub struct AdjacencyList {
@@ -88,94 +237,64 @@ pub mod adjacency_list {
 
         // Takes an Edge List and returns an Adjacency List.
         pub fn from_edge_list(edge_list: &EdgeList) -> AdjacencyList {
+            Self::from_edges(edge_list.edges.iter().map(|&[tail, head]| (tail, head)))
+        }
+
+        // Takes an Edge List and returns the Adjacency List of the graph with every edge
+        // reversed, leaving the Edge List untouched.
+        pub fn from_reversed_edge_list(edge_list: &EdgeList) -> AdjacencyList {
+            Self::from_edges(edge_list.edges.iter().map(|&[tail, head]| (head, tail)))
+        }
+
+        fn from_edges(edges: impl Iterator<Item = (i32, i32)>) -> AdjacencyList {
             let mut adjacencies = HashMap::new();
-            for edge in &edge_list.edges {
+            for (tail, head) in edges {
                 // If starting node is already in graph, then add ending
                 // node; else, add the starting node to the graph along
                 // with the ending node.
                 adjacencies
-                    .entry(edge[0])
-                    .and_modify(|vec: &mut Vec<i32>| vec.push(edge[1]))
-                    .or_insert(vec![edge[1]]);
+                    .entry(tail)
+                    .and_modify(|vec: &mut Vec<i32>| vec.push(head))
+                    .or_insert(vec![head]);
                 // If ending node is already in map, do nothing;
                 // else, insert an empty vector.
                 // This allows us to ensure that isolated vertices still
                 // have an entry in the adjacency list.
-                adjacencies.entry(edge[1]).or_insert(vec![]);
+                adjacencies.entry(head).or_insert(vec![]);
             }
             AdjacencyList { adjacencies }
         }
+    }
 
-        // Performs Depth-First Search on self. If the first node has already been visited or
-        // doesn't exist in the graph, returns None; else, returns Some vector of
-        // all visited nodes ordered by finishing time.
-        pub fn dfs(&self, start: &i32, visited: &mut HashSet<i32>) -> Option<Vec<i32>> {
-            // Helper function to process todo list. If first node in next has been visited,
-            // do nothing and drop it from next. If it has not been visited yet, push itself and
-            // its outgoing edges to the top of the todo list and add to visited. If next is empty,
-            // we are finished processing vertex and may add it to finishing times.
-            fn process_todo<'a>(
-                current_vertex: i32,
-                next_vertices: &mut &'a [i32],
-                todo: &mut Vec<(i32, &'a [i32])>,
-                visited: &mut HashSet<i32>,
-                finishing_times: &mut Vec<i32>,
-                adjacencies: &'a HashMap<i32, Vec<i32>>,
-            ) {
-                match next_vertices {
-                    // If there are no outgoing edges to process, this vertex is finished and so
-                    // we add it to finishing_times.
-                    &mut &[] => finishing_times.push(current_vertex),
-                    // If there is at least one outgoing edge to process, we must check whether it
-                    // has been visited or not. First we push the current vertex and its remaining
-                    // outgoing edges back onto the todo list (we have to do this because we
-                    // can't modify todo both in the calling match expression and also this
-                    // function). Then we check if next_vertex has been visited. If it has, we do
-                    // nothing; else, we push it and its outgoing edges to the todo list.
-                    &mut [next_vertex, rest_vertices @ ..] => {
-                        // Push our current vertex and its remaining outgoing edges back to todo.
-                        todo.push((current_vertex, rest_vertices));
-                        // If next_vertex hasn't already been visited, add it and its outgoing
-                        // edges to the todo list.
-                        if visited.insert(*next_vertex) {
-                            if let Some((&next, next_outgoing)) =
-                                adjacencies.get_key_value(next_vertex)
-                            {
-                                todo.push((next, &next_outgoing[..]))
-                            }
-                        }
-                    }
-                }
-            }
+    impl Graph for AdjacencyList {
+        fn num_nodes(&self) -> usize {
+            AdjacencyList::num_nodes(self)
+        }
 
-            // If start is already visited, whole expression evaluates to true and we return None.
-            if !visited.insert(*start) {
-                return None;
-            };
+        fn sorted_vertices(&self) -> Vec<i32> {
+            let mut vertices: Vec<i32> = self.adjacencies.keys().copied().collect();
+            vertices.sort_unstable();
+            vertices
+        }
 
-            let adjacencies = &self.adjacencies;
-            // If start is not contained in the adjacency list, then return None; else,
-            // initialize todo list with starting vertex and its outgoing edges.
-            let mut todo = vec![match adjacencies.get_key_value(&start) {
-                None => return None,
-                Some((&vertex, edges)) => (vertex, &edges[..]),
-            }];
-            let mut finishing_times: Vec<i32> = vec![];
+        fn successors(&self, vertex: i32) -> Option<&[i32]> {
+            self.adjacencies.get(&vertex).map(|heads| &heads[..])
+        }
 
-            loop {
-                match todo.pop() {
-                    None => break,
-                    Some((current_vertex, mut next_vertices)) => process_todo(
-                        current_vertex,
-                        &mut next_vertices,
-                        &mut todo,
-                        visited,
-                        &mut finishing_times,
-                        adjacencies,
-                    ),
+        fn reversed(&self) -> AdjacencyList {
+            // Start with every vertex so that vertices without incoming edges keep their entry.
+            let mut adjacencies: HashMap<i32, Vec<i32>> =
+                self.adjacencies.keys().map(|&vertex| (vertex, vec![])).collect();
+            // Go through tails in sorted order so the reversed lists don't depend on HashMap
+            // ordering.
+            let mut tails: Vec<&i32> = self.adjacencies.keys().collect();
+            tails.sort_unstable();
+            for tail in tails {
+                for head in &self.adjacencies[tail] {
+                    adjacencies.entry(*head).or_default().push(*tail);
                 }
             }
-            return Some(finishing_times);
+            AdjacencyList { adjacencies }
         }
     }
 }
@@ -184,18 +303,15 @@ pub mod adjacency_list {
 mod tests {
     use crate::adjacency_list::*;
     use crate::edge_list::*;
+    use crate::graph::Graph;
     use std::collections::{HashMap, HashSet};
     use std::vec;
 
     fn setup_edge_list() -> EdgeList {
-        EdgeList {
-            edges: vec![[1, 2], [1, 3]],
-        }
+        EdgeList::new(vec![[1, 2], [1, 3]])
     }
     fn setup_reversed_edgelist() -> EdgeList {
-        EdgeList {
-            edges: vec![[2, 1], [3, 1]],
-        }
+        EdgeList::new(vec![[2, 1], [3, 1]])
     }
     fn setup_adj_list() -> AdjacencyList {
         let mut adjacencies = HashMap::new();
@@ -207,7 +323,7 @@ mod tests {
 
     #[test]
     fn test_parser() {
-        let edges: EdgeList = EdgeList::parse_edge_list("test_file.txt");
+        let edges: EdgeList = EdgeList::parse_edge_list("test_file.txt").unwrap();
 
         assert_eq!(edges, setup_edge_list())
     }
@@ -243,13 +359,99 @@ mod tests {
 
     #[test]
     fn test_scc() {
-        let mut edge_list = setup_edge_list();
+        let edge_list = setup_edge_list();
         assert_eq!(edge_list.scc(), vec![vec![3], vec![2], vec![1]]);
 
-        let mut edge_list_w_cycle = EdgeList::parse_edge_list("test_file2.txt");
+        let edge_list_w_cycle = EdgeList::parse_edge_list("test_file2.txt").unwrap();
         let mut scc = edge_list_w_cycle.scc();
         scc.iter_mut().for_each(|x| x.sort());
         scc.sort_by_key(|x| x[0]);
         assert_eq!(scc, vec![vec![1, 2], vec![3]])
     }
+
+    #[test]
+    fn test_scc_leaves_edge_list_untouched() {
+        let edge_list = setup_edge_list();
+        edge_list.scc();
+        edge_list.condensation();
+        assert_eq!(edge_list, setup_edge_list())
+    }
+
+    #[test]
+    fn test_condensation() {
+        let edge_list = setup_edge_list();
+        let condensation = edge_list.condensation();
+        assert_eq!(condensation.components, vec![vec![1], vec![2], vec![3]]);
+        assert_eq!(condensation.dag, vec![vec![1, 2], vec![], vec![]]);
+
+        let edge_list_w_cycle = EdgeList::parse_edge_list("test_file2.txt").unwrap();
+        let condensation = edge_list_w_cycle.condensation();
+        let mut components = condensation.components.clone();
+        components.iter_mut().for_each(|x| x.sort());
+        assert_eq!(components, vec![vec![1, 2], vec![3]]);
+        assert_eq!(condensation.component_of, HashMap::from([(1, 0), (2, 0), (3, 1)]));
+        assert_eq!(condensation.dag, vec![vec![1], vec![]]);
+    }
+
+    #[test]
+    fn test_condensation_is_topologically_ordered() {
+        // Two 2-cycles joined by parallel edges, plus a vertex feeding into both.
+        let edge_list =
+            EdgeList::new(vec![[1, 2], [2, 1], [3, 4], [4, 3], [2, 3], [1, 4], [5, 1], [5, 3]]);
+        let condensation = edge_list.condensation();
+        assert_eq!(condensation.components.len(), 3);
+        for [tail, head] in &edge_list.edges {
+            assert!(condensation.component_of[tail] <= condensation.component_of[head]);
+        }
+        let id = |v: i32| condensation.component_of[&v];
+        assert_eq!(condensation.dag[id(5)], vec![id(1), id(3)]);
+        assert_eq!(condensation.dag[id(1)], vec![id(3)]);
+        assert!(condensation.dag[id(3)].is_empty());
+    }
+
+    #[test]
+    fn test_parser_weights_and_whitespace() {
+        let edges = EdgeList::parse_edge_list("test_file5.txt").unwrap();
+        assert_eq!(edges.edges, vec![[1, 2], [2, 3], [3, 1]]);
+        assert_eq!(edges.weights, Some(vec![10, -4, 7]));
+    }
+
+    #[test]
+    fn test_parser_from_str() {
+        let edges: EdgeList = "# header\n\n1\t2\n  1   3  # comment\n\n".parse().unwrap();
+        assert_eq!(edges, setup_edge_list());
+        assert_eq!("".parse::<EdgeList>().unwrap(), EdgeList::new(vec![]));
+    }
+
+    #[test]
+    fn test_parser_errors() {
+        assert!(matches!(
+            "1 2\n\n3\n".parse::<EdgeList>(),
+            Err(ParseError::WrongColumnCount { line: 3, found: 1 })
+        ));
+        assert!(matches!(
+            "1 2 3 4".parse::<EdgeList>(),
+            Err(ParseError::WrongColumnCount { line: 1, found: 4 })
+        ));
+        match "1 2\n2 x".parse::<EdgeList>() {
+            Err(ParseError::InvalidNumber { line: 2, column: 2, value }) => assert_eq!(value, "x"),
+            other => panic!("Expected an InvalidNumber error, got {:?}", other),
+        }
+        assert!(matches!(
+            "1 2 5\n2 3 1.5".parse::<EdgeList>(),
+            Err(ParseError::InvalidNumber { line: 2, column: 3, .. })
+        ));
+        assert!(matches!(
+            "1 2 5\n# 2 3\n2 3".parse::<EdgeList>(),
+            Err(ParseError::InconsistentWeights { line: 3 })
+        ));
+        assert!(matches!(
+            EdgeList::parse_edge_list("no_such_file.txt"),
+            Err(ParseError::Io(_))
+        ));
+        assert_eq!(
+            "1 2\n2 x".parse::<EdgeList>().unwrap_err().to_string(),
+            "line 2, column 2: 'x' isn't a number"
+        );
+    }
 }
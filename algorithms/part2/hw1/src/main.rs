@@ -2,7 +2,7 @@ use hw1::{self, edge_list::EdgeList};
 
 fn main() {
     let hw1file = "hw1_SCC.txt";
-    let mut edgelist = EdgeList::parse_edge_list(hw1file);
+    let edgelist = EdgeList::parse_edge_list(hw1file).expect("Couldn't parse the edge list");
     let scc = edgelist.scc();
     let mut scc_sizes: Vec<i32> = scc.iter()
         .map(|x| x.len() as i32)
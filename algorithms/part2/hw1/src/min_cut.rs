@@ -0,0 +1,365 @@
+use crate::edge_list::{parse_column, ParseError};
+use crate::union_find::UnionFind;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap, HashMap},
+    fs::File,
+    io::{BufRead, BufReader},
+};
+use xorshift::XorShift;
+
+// An undirected multigraph, as used for the minimum cut assignment. Vertices are kept sorted.
+// Parallel edges are kept, since each one adds to the size of a cut it crosses; how many there
+// are between two vertices is up to new's caller, or from_reader's input.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UndirectedGraph {
+    vertices: Vec<i32>,
+    // Edges as pairs of indices into vertices, with the smaller index first.
+    edges: Vec<[usize; 2]>,
+}
+
+// A cut of a graph into two non-empty sets of vertices.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Cut {
+    // The number of edges with one endpoint on each side.
+    pub value: usize,
+    // Both sides are sorted, and sides[0] holds the smallest vertex of the graph.
+    pub sides: [Vec<i32>; 2],
+}
+
+impl UndirectedGraph {
+    // Returns the graph with the given vertices and edges. Vertices only mentioned by an edge
+    // are added, and self loops are dropped since they can never cross a cut.
+    pub fn new(vertices: &[i32], edges: &[[i32; 2]]) -> UndirectedGraph {
+        let mut all_vertices: Vec<i32> = vertices
+            .iter()
+            .copied()
+            .chain(edges.iter().flatten().copied())
+            .collect();
+        all_vertices.sort_unstable();
+        all_vertices.dedup();
+        let index = |vertex: &i32| all_vertices.binary_search(vertex).unwrap();
+        let edges = edges
+            .iter()
+            .filter(|[u, v]| u != v)
+            .map(|[u, v]| {
+                let (u, v) = (index(u), index(v));
+                [u.min(v), u.max(v)]
+            })
+            .collect();
+        UndirectedGraph {
+            vertices: all_vertices,
+            edges,
+        }
+    }
+
+    // Reads in a file in the adjacency list format from part 1 and returns an UndirectedGraph.
+    pub fn parse_adjacency_list(filepath: &str) -> Result<UndirectedGraph, ParseError> {
+        UndirectedGraph::from_reader(BufReader::new(File::open(filepath)?))
+    }
+
+    // Reads an adjacency list from reader. Each line starts with a vertex followed by its
+    // neighbours, separated by any whitespace, so every edge is usually listed once at each
+    // endpoint. An edge listed at only one endpoint is kept all the same, so the graph has as
+    // many edges between two vertices as either of them lists. Blank lines are skipped.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<UndirectedGraph, ParseError> {
+        let mut vertices: Vec<i32> = vec![];
+        // listed[&[u, v]], with u <= v, counts how often v is listed as a neighbour of u, and
+        // how often u is listed as a neighbour of v.
+        let mut listed: BTreeMap<[i32; 2], [usize; 2]> = BTreeMap::new();
+
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            if columns.is_empty() {
+                continue;
+            }
+            let vertex: i32 = parse_column(&columns, 0, line_number + 1)?;
+            vertices.push(vertex);
+            for column in 1..columns.len() {
+                let neighbour: i32 = parse_column(&columns, column, line_number + 1)?;
+                let pair = [vertex.min(neighbour), vertex.max(neighbour)];
+                listed.entry(pair).or_default()[usize::from(vertex != pair[0])] += 1;
+            }
+        }
+
+        let edges: Vec<[i32; 2]> = listed
+            .into_iter()
+            .flat_map(|(pair, [from_first, from_second])| {
+                std::iter::repeat_n(pair, from_first.max(from_second))
+            })
+            .collect();
+        Ok(UndirectedGraph::new(&vertices, &edges))
+    }
+
+    // Returns the number of vertices in the graph.
+    pub fn num_nodes(&self) -> usize {
+        self.vertices.len()
+    }
+
+    // Returns the number of edges in the graph, counting parallel edges separately.
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    // Runs Karger's random contraction algorithm trials times and returns the smallest cut
+    // found, or None if the graph has fewer than two vertices. The same seed always gives the
+    // same result. Each trial succeeds with probability at least 2 / n^2, so about
+    // n^2 ln n trials find a minimum cut with high probability.
+    pub fn karger_min_cut(&self, trials: usize, seed: u64) -> Option<Cut> {
+        if self.num_nodes() < 2 {
+            return None;
+        }
+        let mut rng = XorShift::new(seed);
+        let mut edges = self.edges.clone();
+        let mut best: Option<Cut> = None;
+
+        for _ in 0..trials {
+            // Contracting edges in a uniformly random order, skipping any that have become
+            // self loops, is the same as picking a random remaining edge each time.
+            rng.shuffle(&mut edges);
+            let mut components = UnionFind::new(self.num_nodes());
+            for &[u, v] in &edges {
+                if components.count() == 2 {
+                    break;
+                }
+                components.union(u, v);
+            }
+            // If the graph is disconnected we can run out of edges with more than two
+            // components left. Any of them on its own then gives a cut of value 0.
+            let first = components.find(0);
+            let side: Vec<bool> = (0..self.num_nodes())
+                .map(|vertex| components.find(vertex) == first)
+                .collect();
+            let cut = self.cut(&side);
+            if best.as_ref().is_none_or(|best| cut.value < best.value) {
+                best = Some(cut);
+            }
+        }
+
+        best
+    }
+
+    // Finds a minimum cut with the Stoer-Wagner algorithm, or returns None if the graph has
+    // fewer than two vertices. Unlike karger_min_cut it's deterministic and always right. Each
+    // of the n - 1 phases is a maximum adjacency search using a heap, so the whole thing takes
+    // O(n m log n) time.
+    pub fn stoer_wagner_min_cut(&self) -> Option<Cut> {
+        let n = self.num_nodes();
+        if n < 2 {
+            return None;
+        }
+
+        // weights[u][v] is the number of edges between the merged vertices u and v.
+        let mut weights: Vec<HashMap<usize, usize>> = vec![HashMap::new(); n];
+        for &[u, v] in &self.edges {
+            *weights[u].entry(v).or_default() += 1;
+            *weights[v].entry(u).or_default() += 1;
+        }
+        // members[u] holds the original vertices merged into u.
+        let mut members: Vec<Vec<usize>> = (0..n).map(|vertex| vec![vertex]).collect();
+        let mut active: Vec<usize> = (0..n).collect();
+        let mut best: Option<(usize, Vec<usize>)> = None;
+
+        while active.len() > 1 {
+            let (second_last, last, cut_of_the_phase) = maximum_adjacency_search(&active, &weights);
+            if best
+                .as_ref()
+                .is_none_or(|(value, _)| cut_of_the_phase < *value)
+            {
+                best = Some((cut_of_the_phase, members[last].clone()));
+            }
+
+            // Merge last into second_last.
+            let last_weights = std::mem::take(&mut weights[last]);
+            for (neighbour, weight) in last_weights {
+                weights[neighbour].remove(&last);
+                if neighbour != second_last {
+                    *weights[neighbour].entry(second_last).or_default() += weight;
+                    *weights[second_last].entry(neighbour).or_default() += weight;
+                }
+            }
+            let last_members = std::mem::take(&mut members[last]);
+            members[second_last].extend(last_members);
+            active.retain(|&vertex| vertex != last);
+        }
+
+        let (_, best_members) = best.expect("There should be at least one phase");
+        let mut side = vec![false; n];
+        for vertex in best_members {
+            side[vertex] = true;
+        }
+        Some(self.cut(&side))
+    }
+
+    // Returns the cut between the vertices with side[vertex] set and the rest.
+    fn cut(&self, side: &[bool]) -> Cut {
+        let value = self
+            .edges
+            .iter()
+            .filter(|&&[u, v]| side[u] != side[v])
+            .count();
+        let (mut first, mut second): (Vec<i32>, Vec<i32>) = (vec![], vec![]);
+        for (vertex, &in_side) in self.vertices.iter().zip(side) {
+            if in_side == side[0] {
+                first.push(*vertex);
+            } else {
+                second.push(*vertex);
+            }
+        }
+        Cut {
+            value,
+            sides: [first, second],
+        }
+    }
+}
+
+// Runs one phase of Stoer-Wagner: starting from the first active vertex, repeatedly adds the
+// vertex most tightly connected to those added so far. Returns the last two vertices added and
+// the total weight of the edges from the last one to the rest.
+fn maximum_adjacency_search(
+    active: &[usize],
+    weights: &[HashMap<usize, usize>],
+) -> (usize, usize, usize) {
+    let mut connection: HashMap<usize, usize> = active.iter().map(|&vertex| (vertex, 0)).collect();
+    // Entries whose connection has since grown are stale and skipped when popped. Ties go to
+    // the smallest vertex, so the search starts at active[0] and doesn't depend on HashMap
+    // ordering.
+    let mut queue: BinaryHeap<(usize, Reverse<usize>)> =
+        active.iter().map(|&vertex| (0, Reverse(vertex))).collect();
+    let (mut second_last, mut last, mut last_connection) = (active[0], active[0], 0);
+
+    while let Some((vertex_connection, Reverse(vertex))) = queue.pop() {
+        match connection.get(&vertex) {
+            Some(&current) if current == vertex_connection => {}
+            _ => continue,
+        }
+        connection.remove(&vertex);
+        second_last = last;
+        last = vertex;
+        last_connection = vertex_connection;
+        for (neighbour, weight) in &weights[vertex] {
+            if let Some(current) = connection.get_mut(neighbour) {
+                *current += weight;
+                queue.push((*current, Reverse(*neighbour)));
+            }
+        }
+    }
+
+    (second_last, last, last_connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two copies of K4 joined by the edges 1 - 5 and 4 - 8.
+    fn two_cliques() -> UndirectedGraph {
+        let mut edges = vec![[1, 5], [4, 8]];
+        for offset in [0, 4] {
+            for u in 1..=4 {
+                for v in u + 1..=4 {
+                    edges.push([u + offset, v + offset]);
+                }
+            }
+        }
+        UndirectedGraph::new(&[], &edges)
+    }
+
+    fn random_graph(seed: u64, num_vertices: usize, num_edges: usize) -> UndirectedGraph {
+        let mut rng = XorShift::new(seed);
+        let mut vertex = || rng.below(num_vertices) as i32;
+        let edges: Vec<[i32; 2]> = (0..num_edges).map(|_| [vertex(), vertex()]).collect();
+        let vertices: Vec<i32> = (0..num_vertices as i32).collect();
+        UndirectedGraph::new(&vertices, &edges)
+    }
+
+    #[test]
+    fn test_parse_adjacency_list() {
+        let graph =
+            UndirectedGraph::from_reader("1\t2\t3\t\n2\t1\t3\n\n3 1 2\n4\n".as_bytes()).unwrap();
+        assert_eq!(graph, UndirectedGraph::new(&[4], &[[1, 2], [1, 3], [2, 3]]));
+
+        // Edges listed at only one endpoint, whichever it is, are kept, and parallel edges
+        // count as many times as either endpoint lists them.
+        let graph = UndirectedGraph::from_reader("2\t1\n3\t1\t2\t2\n1\t3\n".as_bytes()).unwrap();
+        assert_eq!(
+            graph,
+            UndirectedGraph::new(&[], &[[1, 2], [1, 3], [2, 3], [2, 3]])
+        );
+
+        assert!(matches!(
+            UndirectedGraph::from_reader("1\t2\n2\tx\n".as_bytes()),
+            Err(ParseError::InvalidNumber {
+                line: 2,
+                column: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_min_cut() {
+        let expected = Cut {
+            value: 2,
+            sides: [vec![1, 2, 3, 4], vec![5, 6, 7, 8]],
+        };
+        let graph = two_cliques();
+        assert_eq!(graph.stoer_wagner_min_cut(), Some(expected));
+        assert_eq!(graph.karger_min_cut(50, 1).unwrap().value, 2);
+    }
+
+    #[test]
+    fn test_min_cut_small_graphs() {
+        let graph = UndirectedGraph::new(&[1], &[]);
+        assert_eq!(graph.stoer_wagner_min_cut(), None);
+        assert_eq!(graph.karger_min_cut(10, 1), None);
+
+        // A disconnected graph can be cut without crossing any edges.
+        let graph = UndirectedGraph::new(&[], &[[1, 2], [3, 4], [4, 5]]);
+        let expected = Cut {
+            value: 0,
+            sides: [vec![1, 2], vec![3, 4, 5]],
+        };
+        assert_eq!(graph.stoer_wagner_min_cut(), Some(expected));
+        assert_eq!(graph.karger_min_cut(10, 1).unwrap().value, 0);
+
+        // Parallel edges all count towards the cut.
+        let graph = UndirectedGraph::new(&[], &[[1, 2], [2, 1], [2, 3], [3, 3]]);
+        let expected = Cut {
+            value: 1,
+            sides: [vec![1, 2], vec![3]],
+        };
+        assert_eq!(graph.stoer_wagner_min_cut(), Some(expected));
+        assert_eq!(graph.karger_min_cut(10, 1).unwrap().value, 1);
+    }
+
+    #[test]
+    fn test_karger_is_reproducible() {
+        let graph = random_graph(7, 30, 80);
+        assert_eq!(graph.karger_min_cut(20, 42), graph.karger_min_cut(20, 42));
+    }
+
+    #[test]
+    fn test_karger_matches_stoer_wagner() {
+        for seed in 1..=20 {
+            let graph = random_graph(seed, 12, 30);
+            let stoer_wagner = graph.stoer_wagner_min_cut().unwrap();
+            let karger = graph.karger_min_cut(300, seed).unwrap();
+            assert_eq!(karger.value, stoer_wagner.value);
+            for cut in [karger, stoer_wagner] {
+                assert!(!cut.sides[0].is_empty() && !cut.sides[1].is_empty());
+                assert_eq!(cut.sides[0].len() + cut.sides[1].len(), graph.num_nodes());
+            }
+        }
+    }
+
+    #[test]
+    fn test_assignment_graph() {
+        let graph =
+            UndirectedGraph::parse_adjacency_list("../../part1/hw4_adjacency_list.txt").unwrap();
+        assert_eq!(graph.num_nodes(), 200);
+        assert_eq!(graph.stoer_wagner_min_cut().unwrap().value, 17);
+        assert_eq!(graph.karger_min_cut(200, 1).unwrap().value, 17);
+    }
+}
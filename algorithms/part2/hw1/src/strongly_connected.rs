@@ -0,0 +1,363 @@
+use crate::graph::{dfs_marking, Graph, VisitedVertices};
+use std::collections::HashMap;
+
+// An algorithm for finding the strongly connected components of a directed graph G.
+pub trait StronglyConnected<G: Graph> {
+    // Returns the strongly connected components of graph. Each element of the result is a
+    // vector of the vertices making up one component. Components are ordered so that sink
+    // components come first, i.e. in reverse topological order of the condensation graph.
+    fn scc(&self, graph: &G) -> Vec<Vec<i32>>;
+}
+
+// Kosaraju's two-pass algorithm. Needs the reversed graph as well, so it holds two copies of
+// the graph in memory at once.
+pub struct Kosaraju;
+
+// Tarjan's algorithm, run iteratively. Finds every component in a single DFS using low-link
+// values.
+pub struct Tarjan;
+
+// Gabow's path-based algorithm. Finds every component in a single DFS using a second stack of
+// possible component roots instead of low-link values.
+pub struct PathBased;
+
+impl<G: Graph> StronglyConnected<G> for Kosaraju {
+    fn scc(&self, graph: &G) -> Vec<Vec<i32>> {
+        kosaraju(graph, &graph.reversed())
+    }
+}
+
+impl<G: Graph> StronglyConnected<G> for Tarjan {
+    fn scc(&self, graph: &G) -> Vec<Vec<i32>> {
+        let mut search = TarjanSearch::new(graph);
+
+        for start in graph.sorted_vertices() {
+            if search.index.contains_key(&start) {
+                continue;
+            }
+            search.discover(start);
+
+            while let Some((vertex, next_vertices)) = search.todo.last_mut() {
+                let vertex = *vertex;
+                let vertex_number = search.index[&vertex];
+                match *next_vertices {
+                    &[next_vertex, ref rest_vertices @ ..] => {
+                        *next_vertices = rest_vertices;
+                        match search.index.get(&next_vertex) {
+                            None => search.discover(next_vertex),
+                            Some(&next_number) if search.on_stack[next_number] => {
+                                search.lower_link(vertex_number, next_number)
+                            }
+                            // next_vertex belongs to a component we've already finished.
+                            Some(_) => {}
+                        }
+                    }
+                    [] => {
+                        search.todo.pop();
+                        if let Some(&(parent, _)) = search.todo.last() {
+                            let parent_number = search.index[&parent];
+                            search.lower_link(parent_number, search.low_link[vertex_number]);
+                        }
+                        // vertex is the root of a component, which consists of everything
+                        // above it on the stack.
+                        if search.low_link[vertex_number] == vertex_number {
+                            search.pop_component(vertex);
+                        }
+                    }
+                }
+            }
+        }
+
+        search.result
+    }
+}
+
+// State for an iterative run of Tarjan's algorithm. Vertices are numbered in the order they are
+// discovered, and all per-vertex state other than that number is stored in vectors indexed by
+// it, so only one HashMap is needed.
+struct TarjanSearch<'a, G> {
+    graph: &'a G,
+    index: HashMap<i32, usize>,
+    low_link: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<i32>,
+    // Each entry holds a vertex and the outgoing edges we haven't followed yet.
+    todo: Vec<(i32, &'a [i32])>,
+    result: Vec<Vec<i32>>,
+}
+
+impl<'a, G: Graph> TarjanSearch<'a, G> {
+    fn new(graph: &'a G) -> Self {
+        let num_nodes = graph.num_nodes();
+        Self {
+            graph,
+            index: HashMap::with_capacity(num_nodes),
+            low_link: Vec::with_capacity(num_nodes),
+            on_stack: Vec::with_capacity(num_nodes),
+            stack: vec![],
+            todo: vec![],
+            result: vec![],
+        }
+    }
+
+    // Numbers vertex and pushes it onto both stacks.
+    fn discover(&mut self, vertex: i32) {
+        let number = self.low_link.len();
+        self.index.insert(vertex, number);
+        self.low_link.push(number);
+        self.on_stack.push(true);
+        self.stack.push(vertex);
+        self.todo
+            .push((vertex, self.graph.successors(vertex).unwrap_or_default()));
+    }
+
+    fn lower_link(&mut self, vertex_number: usize, candidate: usize) {
+        self.low_link[vertex_number] = self.low_link[vertex_number].min(candidate)
+    }
+
+    // Pops everything down to and including root off the stack as one component.
+    fn pop_component(&mut self, root: i32) {
+        let mut component = vec![];
+        while let Some(member) = self.stack.pop() {
+            self.on_stack[self.index[&member]] = false;
+            component.push(member);
+            if member == root {
+                break;
+            }
+        }
+        self.result.push(component);
+    }
+}
+
+impl<G: Graph> StronglyConnected<G> for PathBased {
+    fn scc(&self, graph: &G) -> Vec<Vec<i32>> {
+        let mut search = PathBasedSearch::new(graph);
+
+        for start in graph.sorted_vertices() {
+            if search.preorder.contains_key(&start) {
+                continue;
+            }
+            search.discover(start);
+
+            while let Some((vertex, next_vertices)) = search.todo.last_mut() {
+                let vertex = *vertex;
+                match *next_vertices {
+                    &[next_vertex, ref rest_vertices @ ..] => {
+                        *next_vertices = rest_vertices;
+                        match search.preorder.get(&next_vertex) {
+                            None => search.discover(next_vertex),
+                            // We found a path back into the current DFS path, so every
+                            // possible root discovered after next_vertex is part of its
+                            // component.
+                            Some(&next_number) if !search.assigned[next_number] => {
+                                search.merge_roots(next_number)
+                            }
+                            Some(_) => {}
+                        }
+                    }
+                    [] => {
+                        search.todo.pop();
+                        if search.roots.last() == Some(&vertex) {
+                            search.roots.pop();
+                            search.pop_component(vertex);
+                        }
+                    }
+                }
+            }
+        }
+
+        search.result
+    }
+}
+
+// State for an iterative run of the path-based algorithm. As in TarjanSearch, vertices are
+// numbered in the order they are discovered and other per-vertex state is indexed by that
+// number.
+struct PathBasedSearch<'a, G> {
+    graph: &'a G,
+    preorder: HashMap<i32, usize>,
+    assigned: Vec<bool>,
+    // Vertices that haven't been assigned to a component yet.
+    stack: Vec<i32>,
+    // Vertices that might still turn out to be the root of a component.
+    roots: Vec<i32>,
+    // Each entry holds a vertex and the outgoing edges we haven't followed yet.
+    todo: Vec<(i32, &'a [i32])>,
+    result: Vec<Vec<i32>>,
+}
+
+impl<'a, G: Graph> PathBasedSearch<'a, G> {
+    fn new(graph: &'a G) -> Self {
+        let num_nodes = graph.num_nodes();
+        Self {
+            graph,
+            preorder: HashMap::with_capacity(num_nodes),
+            assigned: Vec::with_capacity(num_nodes),
+            stack: vec![],
+            roots: vec![],
+            todo: vec![],
+            result: vec![],
+        }
+    }
+
+    // Numbers vertex and pushes it onto both stacks.
+    fn discover(&mut self, vertex: i32) {
+        self.preorder.insert(vertex, self.assigned.len());
+        self.assigned.push(false);
+        self.stack.push(vertex);
+        self.roots.push(vertex);
+        self.todo
+            .push((vertex, self.graph.successors(vertex).unwrap_or_default()));
+    }
+
+    // Pops every possible root discovered after the vertex numbered target_number, since they
+    // all lie on a cycle through it.
+    fn merge_roots(&mut self, target_number: usize) {
+        while let Some(root) = self.roots.last() {
+            if self.preorder[root] <= target_number {
+                break;
+            }
+            self.roots.pop();
+        }
+    }
+
+    // Pops everything down to and including root off the stack as one component.
+    fn pop_component(&mut self, root: i32) {
+        let mut component = vec![];
+        while let Some(member) = self.stack.pop() {
+            self.assigned[self.preorder[&member]] = true;
+            component.push(member);
+            if member == root {
+                break;
+            }
+        }
+        self.result.push(component);
+    }
+}
+
+// Runs Kosaraju's algorithm given a graph and its reversal. Components are returned sink
+// components first.
+pub(crate) fn kosaraju<G: Graph>(graph: &G, reversed_graph: &G) -> Vec<Vec<i32>> {
+    let mut visited = VisitedVertices::new(reversed_graph);
+    let mut finishing_times: Vec<i32> = vec![];
+
+    // Visit vertices in sorted order so the result doesn't depend on HashMap ordering.
+    for vertex in reversed_graph.sorted_vertices() {
+        if let Some(mut vertices) = dfs_marking(reversed_graph, vertex, |v| visited.insert(v)) {
+            finishing_times.append(&mut vertices);
+        }
+    }
+
+    let mut visited = VisitedVertices::new(graph);
+    let mut result: Vec<Vec<i32>> = vec![];
+
+    for &vertex in finishing_times.iter().rev() {
+        if let Some(visited_vertices) = dfs_marking(graph, vertex, |v| visited.insert(v)) {
+            result.push(visited_vertices);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjacency_list::AdjacencyList;
+    use crate::csr_graph::CsrGraph;
+    use crate::edge_list::EdgeList;
+    use xorshift::XorShift;
+
+    fn random_edge_list(seed: u64, num_vertices: usize, num_edges: usize) -> EdgeList {
+        let mut rng = XorShift::new(seed);
+        let mut vertex = || rng.below(num_vertices) as i32;
+        EdgeList::new((0..num_edges).map(|_| [vertex(), vertex()]).collect())
+    }
+
+    fn normalize(mut components: Vec<Vec<i32>>) -> Vec<Vec<i32>> {
+        components.iter_mut().for_each(|x| x.sort());
+        components.sort();
+        components
+    }
+
+    // Checks that no edge goes from a component to one that was returned after it.
+    fn assert_reverse_topological<G: Graph>(graph: &G, components: &[Vec<i32>]) {
+        let position: HashMap<i32, usize> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(i, vertices)| vertices.iter().map(move |&vertex| (vertex, i)))
+            .collect();
+        for tail in graph.sorted_vertices() {
+            for head in graph.successors(tail).unwrap() {
+                assert!(position[&tail] >= position[head]);
+            }
+        }
+    }
+
+    fn algorithms<G: Graph>() -> Vec<Box<dyn StronglyConnected<G>>> {
+        vec![Box::new(Kosaraju), Box::new(Tarjan), Box::new(PathBased)]
+    }
+
+    #[test]
+    fn test_test_files() {
+        for algorithm in algorithms() {
+            let graph =
+                AdjacencyList::from_edge_list(&EdgeList::parse_edge_list("test_file.txt").unwrap());
+            assert_eq!(normalize(algorithm.scc(&graph)), vec![vec![1], vec![2], vec![3]]);
+
+            let graph = AdjacencyList::from_edge_list(
+                &EdgeList::parse_edge_list("test_file2.txt").unwrap(),
+            );
+            assert_eq!(normalize(algorithm.scc(&graph)), vec![vec![1, 2], vec![3]]);
+        }
+    }
+
+    #[test]
+    fn test_negative_and_sparse_vertices() {
+        let edge_list = EdgeList::new(vec![[-1, -2], [-2, -1], [1, -1], [i32::MAX, 1]]);
+        let expected = vec![vec![-2, -1], vec![1], vec![i32::MAX]];
+        let graph = AdjacencyList::from_edge_list(&edge_list);
+        for algorithm in algorithms() {
+            assert_eq!(normalize(algorithm.scc(&graph)), expected);
+        }
+        let graph = CsrGraph::from_edge_list(&edge_list);
+        for algorithm in algorithms() {
+            assert_eq!(normalize(algorithm.scc(&graph)), expected);
+        }
+    }
+
+    #[test]
+    fn test_nested_cycles() {
+        // 1 -> 2 -> 3 -> 1 and 3 -> 4 -> 5 -> 4, with 5 -> 6 a sink and a self loop on 6.
+        let edge_list =
+            EdgeList::new(vec![[1, 2], [2, 3], [3, 1], [3, 4], [4, 5], [5, 4], [5, 6], [6, 6]]);
+        let graph = AdjacencyList::from_edge_list(&edge_list);
+        for algorithm in algorithms() {
+            let components = algorithm.scc(&graph);
+            assert_reverse_topological(&graph, &components);
+            assert_eq!(normalize(components), vec![vec![1, 2, 3], vec![4, 5], vec![6]]);
+        }
+    }
+
+    #[test]
+    fn test_algorithms_agree_on_random_graphs() {
+        for seed in 1..=50 {
+            let edge_list = random_edge_list(seed, 30, 60);
+            let expected = normalize(edge_list.scc());
+
+            let graph = AdjacencyList::from_edge_list(&edge_list);
+            for algorithm in algorithms() {
+                let components = algorithm.scc(&graph);
+                assert_reverse_topological(&graph, &components);
+                assert_eq!(normalize(components), expected);
+            }
+
+            let graph = CsrGraph::from_edge_list(&edge_list);
+            for algorithm in algorithms() {
+                let components = algorithm.scc(&graph);
+                assert_reverse_topological(&graph, &components);
+                assert_eq!(normalize(components), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use crate::adjacency_list::AdjacencyList;
+use crate::graph::Graph;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    fmt,
+};
+
+// A cycle found while trying to order a graph. Each vertex has an edge to the next one, and the
+// last vertex has an edge back to the first, so a self loop is a Cycle of one vertex.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Cycle(pub Vec<i32>);
+
+impl fmt::Display for Cycle {
+    // Formats the cycle as e.g. "1 -> 2 -> 3 -> 1".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for vertex in &self.0 {
+            write!(f, "{} -> ", vertex)?;
+        }
+        match self.0.first() {
+            Some(first) => write!(f, "{}", first),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for Cycle {}
+
+impl AdjacencyList {
+    // Returns the vertices of the graph ordered so that every edge goes from an earlier vertex
+    // to a later one, or the first Cycle found if there is no such order. Uses DFS, so the
+    // order is the reverse of the finishing times.
+    pub fn topological_order(&self) -> Result<Vec<i32>, Cycle> {
+        let mut finished: HashSet<i32> = HashSet::new();
+        // The vertices on the current DFS path. Reaching one of these again means we've found a
+        // cycle.
+        let mut on_path: HashSet<i32> = HashSet::new();
+        let mut finishing_times: Vec<i32> = Vec::with_capacity(self.num_nodes());
+
+        for start in self.sorted_vertices() {
+            if finished.contains(&start) {
+                continue;
+            }
+            on_path.insert(start);
+            // Each entry holds a vertex on the current path and the outgoing edges we haven't
+            // followed yet, so the vertices in todo are exactly the current path.
+            let mut todo: Vec<(i32, &[i32])> = vec![(start, &self.adjacencies[&start][..])];
+
+            while let Some((vertex, next_vertices)) = todo.last_mut() {
+                match *next_vertices {
+                    &[next_vertex, ref rest_vertices @ ..] => {
+                        *next_vertices = rest_vertices;
+                        if on_path.contains(&next_vertex) {
+                            let cycle_start = todo
+                                .iter()
+                                .position(|&(path_vertex, _)| path_vertex == next_vertex)
+                                .expect("Every vertex on the path should be in todo");
+                            let cycle = todo[cycle_start..].iter().map(|&(v, _)| v).collect();
+                            return Err(Cycle(cycle));
+                        }
+                        if !finished.contains(&next_vertex) {
+                            on_path.insert(next_vertex);
+                            todo.push((next_vertex, &self.adjacencies[&next_vertex][..]));
+                        }
+                    }
+                    [] => {
+                        let vertex = *vertex;
+                        todo.pop();
+                        on_path.remove(&vertex);
+                        finished.insert(vertex);
+                        finishing_times.push(vertex);
+                    }
+                }
+            }
+        }
+
+        finishing_times.reverse();
+        Ok(finishing_times)
+    }
+
+    // Like topological_order, but uses Kahn's algorithm, always picking the smallest vertex
+    // with no remaining incoming edges next. This gives the lexicographically smallest
+    // topological order, at the cost of a priority queue.
+    pub fn kahn_topological_order(&self) -> Result<Vec<i32>, Cycle> {
+        let mut in_degrees: HashMap<i32, usize> =
+            self.adjacencies.keys().map(|&vertex| (vertex, 0)).collect();
+        for head in self.adjacencies.values().flatten() {
+            *in_degrees
+                .get_mut(head)
+                .expect("Every head should be a vertex") += 1;
+        }
+
+        let mut ready: BinaryHeap<Reverse<i32>> = in_degrees
+            .iter()
+            .filter(|&(_, &in_degree)| in_degree == 0)
+            .map(|(&vertex, _)| Reverse(vertex))
+            .collect();
+        let mut order: Vec<i32> = Vec::with_capacity(self.num_nodes());
+
+        while let Some(Reverse(vertex)) = ready.pop() {
+            order.push(vertex);
+            for head in &self.adjacencies[&vertex] {
+                let in_degree = in_degrees
+                    .get_mut(head)
+                    .expect("Every head should be a vertex");
+                *in_degree -= 1;
+                if *in_degree == 0 {
+                    ready.push(Reverse(*head));
+                }
+            }
+        }
+
+        if order.len() == self.num_nodes() {
+            Ok(order)
+        } else {
+            // Every vertex left over lies on or after a cycle, so the DFS is bound to find one.
+            Err(self
+                .topological_order()
+                .expect_err("A graph Kahn's algorithm can't order should have a cycle"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge_list::EdgeList;
+
+    fn graph(edges: Vec<[i32; 2]>) -> AdjacencyList {
+        AdjacencyList::from_edge_list(&EdgeList::new(edges))
+    }
+
+    // Checks that order contains every vertex once and every edge points forwards.
+    fn assert_topological(graph: &AdjacencyList, order: &[i32]) {
+        let position: HashMap<i32, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(i, &vertex)| (vertex, i))
+            .collect();
+        assert_eq!(order.len(), graph.num_nodes());
+        assert_eq!(position.len(), graph.num_nodes());
+        for (tail, heads) in &graph.adjacencies {
+            for head in heads {
+                assert!(position[tail] < position[head]);
+            }
+        }
+    }
+
+    // Checks that cycle really is a cycle of graph.
+    fn assert_cycle(graph: &AdjacencyList, cycle: &Cycle) {
+        let Cycle(vertices) = cycle;
+        assert!(!vertices.is_empty());
+        for (i, tail) in vertices.iter().enumerate() {
+            let head = vertices[(i + 1) % vertices.len()];
+            assert!(graph.adjacencies[tail].contains(&head));
+        }
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let graph =
+            AdjacencyList::from_edge_list(&EdgeList::parse_edge_list("test_file.txt").unwrap());
+        assert_eq!(graph.topological_order(), Ok(vec![1, 3, 2]));
+        assert_eq!(graph.kahn_topological_order(), Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_build_steps() {
+        // 5 and 1 have no dependencies; 4 depends on everything else.
+        let graph = graph(vec![[5, 2], [1, 2], [2, 3], [3, 4], [1, 4], [5, 4], [6, 3]]);
+        let order = graph.topological_order().unwrap();
+        assert_topological(&graph, &order);
+        assert_eq!(graph.kahn_topological_order(), Ok(vec![1, 5, 2, 6, 3, 4]));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let graph =
+            AdjacencyList::from_edge_list(&EdgeList::parse_edge_list("test_file2.txt").unwrap());
+        assert_eq!(graph.topological_order(), Err(Cycle(vec![1, 2])));
+        assert_eq!(graph.kahn_topological_order(), Err(Cycle(vec![1, 2])));
+        assert_eq!(Cycle(vec![1, 2]).to_string(), "1 -> 2 -> 1");
+    }
+
+    #[test]
+    fn test_cycle_behind_a_dag() {
+        // 1 and 2 come first without problems, but 3 -> 4 -> 5 -> 3 can't be ordered.
+        let graph = graph(vec![[1, 2], [2, 3], [3, 4], [4, 5], [5, 3], [5, 6]]);
+        let cycle = graph.topological_order().unwrap_err();
+        assert_cycle(&graph, &cycle);
+        assert_eq!(cycle, Cycle(vec![3, 4, 5]));
+        assert_cycle(&graph, &graph.kahn_topological_order().unwrap_err());
+    }
+
+    #[test]
+    fn test_self_loop() {
+        let graph = graph(vec![[1, 2], [2, 2]]);
+        assert_eq!(graph.topological_order(), Err(Cycle(vec![2])));
+        assert_eq!(Cycle(vec![2]).to_string(), "2 -> 2");
+    }
+}
@@ -0,0 +1,109 @@
+// An entry in the union-find structure over vertices. Users don't need to use this.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+struct UnionFindEntry {
+    rank: usize,
+    parent: usize,
+}
+
+// A union-find structure over the indices 0..len(), with union by rank and path compression,
+// that keeps count of its components. Used to merge components in IncrementalScc and to
+// contract edges in Karger's algorithm.
+#[derive(Debug, Clone)]
+pub(crate) struct UnionFind {
+    entries: Vec<UnionFindEntry>,
+    count: usize,
+}
+
+impl UnionFind {
+    // Creates a union-find with n indices, each in a component of its own.
+    pub(crate) fn new(n: usize) -> UnionFind {
+        let mut union_find = UnionFind::with_capacity(n);
+        for _ in 0..n {
+            union_find.push();
+        }
+        union_find
+    }
+
+    // Creates an empty union-find with room for capacity indices.
+    pub(crate) fn with_capacity(capacity: usize) -> UnionFind {
+        UnionFind {
+            entries: Vec::with_capacity(capacity),
+            count: 0,
+        }
+    }
+
+    // Adds a new index in a component of its own and returns it.
+    pub(crate) fn push(&mut self) -> usize {
+        let index = self.entries.len();
+        self.entries.push(UnionFindEntry {
+            rank: 0,
+            parent: index,
+        });
+        self.count += 1;
+        index
+    }
+
+    // Returns the number of components.
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+
+    // Finds the root of the component containing index. Rewires every entry along the way to
+    // point straight at the root (path compression).
+    pub(crate) fn find(&mut self, index: usize) -> usize {
+        let mut root = index;
+        while self.entries[root].parent != root {
+            root = self.entries[root].parent;
+        }
+        let mut current = index;
+        while current != root {
+            let next = self.entries[current].parent;
+            self.entries[current].parent = root;
+            current = next;
+        }
+        root
+    }
+
+    // Merges the components containing u and v, attaching the lower rank root to the higher.
+    pub(crate) fn union(&mut self, u: usize, v: usize) {
+        let (u_root, v_root) = (self.find(u), self.find(v));
+        if u_root == v_root {
+            return;
+        }
+        let (u_rank, v_rank) = (self.entries[u_root].rank, self.entries[v_root].rank);
+        if u_rank < v_rank {
+            self.entries[u_root].parent = v_root;
+        } else {
+            self.entries[v_root].parent = u_root;
+            if u_rank == v_rank {
+                self.entries[u_root].rank += 1;
+            }
+        }
+        self.count -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_find() {
+        let mut union_find = UnionFind::new(5);
+        assert_eq!(union_find.count(), 5);
+        union_find.union(0, 1);
+        union_find.union(3, 4);
+        union_find.union(1, 0);
+        assert_eq!(union_find.count(), 3);
+        assert_eq!(union_find.find(0), union_find.find(1));
+        assert_ne!(union_find.find(1), union_find.find(2));
+
+        union_find.union(4, 1);
+        assert_eq!(union_find.count(), 2);
+        assert_eq!(union_find.find(3), union_find.find(0));
+
+        assert_eq!(union_find.push(), 5);
+        assert_eq!(union_find.count(), 3);
+        assert_eq!(union_find.find(5), 5);
+    }
+}
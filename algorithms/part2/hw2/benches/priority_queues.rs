@@ -0,0 +1,50 @@
+// Compares dijkstra, which pushes a node again every time it finds a shorter path to it, against
+// dijkstra_indexed, which lowers the node's key in an IndexedHeap instead. Runs both from every
+// node of dijkstraData.txt.
+//
+// Run with `cargo bench`.
+use hw2::AdjacencyList;
+use std::time::Instant;
+
+const ROUNDS: usize = 20;
+
+// Runs f ROUNDS times and prints how long that took.
+fn time(label: &str, mut f: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    println!("{:<40} {:>10.2?}", label, start.elapsed());
+}
+
+fn main() {
+    let graph: AdjacencyList = AdjacencyList::parse_adjacencylist("dijkstraData.txt");
+    println!(
+        "{} nodes, {} edges, {} rounds of every source",
+        graph.num_nodes() - 1,
+        graph.num_edges(),
+        ROUNDS
+    );
+    // The lazy queue gets an entry for every edge that improves a distance, while the indexed
+    // heap only ever holds nodes.
+    let (mut lazy_len, mut indexed_len) = (0, 0);
+    for source in 1..graph.num_nodes() {
+        lazy_len = lazy_len.max(graph.dijkstra_with_max_queue_len(source).unwrap().1);
+        indexed_len = indexed_len.max(graph.dijkstra_indexed_with_max_queue_len(source).unwrap().1);
+    }
+    println!(
+        "largest queue over every source: BinaryHeap {}, IndexedHeap {}",
+        lazy_len, indexed_len
+    );
+
+    time("dijkstra (BinaryHeap)", || {
+        for source in 1..graph.num_nodes() {
+            graph.dijkstra(source).unwrap();
+        }
+    });
+    time("dijkstra_indexed (IndexedHeap)", || {
+        for source in 1..graph.num_nodes() {
+            graph.dijkstra_indexed(source).unwrap();
+        }
+    });
+}
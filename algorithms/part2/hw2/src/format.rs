@@ -0,0 +1,386 @@
+// Reading and writing the weighted adjacency list format, where each line holds a vertex
+// followed by the edges leaving it as destination,dist pairs, all separated by whitespace:
+//
+//     1	2,30	3,12
+//     2	3,40	1,10
+use crate::{AdjacencyList, Edge};
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::str::FromStr;
+
+// The largest vertex or destination from_reader accepts. The graph gets a node for every
+// number up to the largest one mentioned, so without a limit a single mistyped id could ask
+// for more memory than the machine has.
+pub const MAX_VERTEX: usize = (1 << 24) - 1;
+
+// Everything that can go wrong while parsing an adjacency list. Lines are numbered from 1, and
+// columns count the whitespace-separated parts of a line, also from 1, like hw1's edge list
+// errors, so the vertex is column 1.
+#[derive(Debug)]
+pub enum ParseError {
+    // The input couldn't be read.
+    Io(io::Error),
+    // A vertex, destination or dist couldn't be parsed.
+    InvalidNumber {
+        line: usize,
+        column: usize,
+        value: String,
+    },
+    // An edge wasn't of the form destination,dist.
+    InvalidEdge {
+        line: usize,
+        column: usize,
+        value: String,
+    },
+    // A vertex or destination was larger than MAX_VERTEX.
+    VertexTooLarge {
+        line: usize,
+        column: usize,
+        vertex: usize,
+    },
+    // A vertex had its edges listed on more than one line.
+    DuplicateVertex {
+        line: usize,
+        vertex: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(error) => write!(f, "couldn't read adjacency list: {}", error),
+            ParseError::InvalidNumber {
+                line,
+                column,
+                value,
+            } => {
+                write!(
+                    f,
+                    "line {}, column {}: '{}' isn't a number",
+                    line, column, value
+                )
+            }
+            ParseError::InvalidEdge {
+                line,
+                column,
+                value,
+            } => write!(
+                f,
+                "line {}, column {}: expected an edge like 'destination,dist', found '{}'",
+                line, column, value
+            ),
+            ParseError::VertexTooLarge {
+                line,
+                column,
+                vertex,
+            } => write!(
+                f,
+                "line {}, column {}: vertex {} is larger than the limit of {}",
+                line, column, vertex, MAX_VERTEX
+            ),
+            ParseError::DuplicateVertex { line, vertex } => write!(
+                f,
+                "line {}: the edges of vertex {} were already listed",
+                line, vertex
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(error: io::Error) -> Self {
+        ParseError::Io(error)
+    }
+}
+
+// Parses value, found in the given line and column, as a number.
+fn parse_number<T: FromStr>(value: &str, line: usize, column: usize) -> Result<T, ParseError> {
+    value.parse().map_err(|_| ParseError::InvalidNumber {
+        line,
+        column,
+        value: value.to_string(),
+    })
+}
+
+// Parses value, found in the given line and column, as a vertex no larger than MAX_VERTEX.
+fn parse_vertex(value: &str, line: usize, column: usize) -> Result<usize, ParseError> {
+    let vertex = parse_number(value, line, column)?;
+    if vertex > MAX_VERTEX {
+        return Err(ParseError::VertexTooLarge {
+            line,
+            column,
+            vertex,
+        });
+    }
+    Ok(vertex)
+}
+
+impl<W: FromStr> AdjacencyList<W> {
+    // Reads in a file in the adjacency list format, reporting what's wrong with it if it can't.
+    pub fn read_adjacencylist(filename: &str) -> Result<AdjacencyList<W>, ParseError> {
+        AdjacencyList::from_reader(BufReader::new(File::open(filename)?))
+    }
+
+    // Reads an adjacency list from reader. The vertex at the start of each line says whose
+    // edges the line holds, so lines can come in any order and vertices with no edges can be
+    // left out. Blank lines are skipped. The graph has a node for every number up to the
+    // largest vertex or destination mentioned, which can be at most MAX_VERTEX.
+    pub fn from_reader<R: BufRead>(mut reader: R) -> Result<AdjacencyList<W>, ParseError> {
+        let mut adj_list: Vec<Option<Vec<Edge<W>>>> = vec![];
+        let mut line = String::new();
+        let mut line_number = 0;
+
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            line_number += 1;
+
+            let mut columns = line.split_whitespace();
+            let vertex = match columns.next() {
+                Some(vertex) => parse_vertex(vertex, line_number, 1)?,
+                None => continue,
+            };
+            let mut edges = vec![];
+            for (column, edge) in (2..).zip(columns) {
+                let (destination, dist) =
+                    edge.split_once(',')
+                        .ok_or_else(|| ParseError::InvalidEdge {
+                            line: line_number,
+                            column,
+                            value: edge.to_string(),
+                        })?;
+                edges.push(Edge {
+                    destination: parse_vertex(destination, line_number, column)?,
+                    dist: parse_number(dist, line_number, column)?,
+                });
+            }
+
+            let num_nodes = edges
+                .iter()
+                .map(|edge| edge.destination)
+                .chain([vertex])
+                .max()
+                .map_or(0, |largest| largest + 1);
+            if adj_list.len() < num_nodes {
+                adj_list.resize_with(num_nodes, || None);
+            }
+            if adj_list[vertex].is_some() {
+                return Err(ParseError::DuplicateVertex {
+                    line: line_number,
+                    vertex,
+                });
+            }
+            adj_list[vertex] = Some(edges);
+        }
+
+        Ok(AdjacencyList(
+            adj_list
+                .into_iter()
+                .map(Option::unwrap_or_default)
+                .collect(),
+        ))
+    }
+}
+
+impl<W: FromStr> FromStr for AdjacencyList<W> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AdjacencyList::from_reader(s.as_bytes())
+    }
+}
+
+impl<W: Display> AdjacencyList<W> {
+    // Writes the graph to writer in the adjacency list format, one line per node, separated by
+    // tabs like the assignment's files. Reading it back in gives the same graph. The files
+    // number vertices from 1, so node 0 is left out unless it has edges of its own.
+    pub fn write_to<T: Write>(&self, mut writer: T) -> io::Result<()> {
+        let AdjacencyList(adj_list) = self;
+        for (vertex, edges) in adj_list.iter().enumerate() {
+            if vertex == 0 && edges.is_empty() {
+                continue;
+            }
+            write!(writer, "{}", vertex)?;
+            for edge in edges {
+                write!(writer, "\t{},{}", edge.destination, edge.dist)?;
+            }
+            writeln!(writer)?;
+        }
+        writer.flush()
+    }
+
+    // Writes the graph to a file in the adjacency list format, replacing the file if it
+    // already exists.
+    pub fn save_adjacencylist(&self, filename: &str) -> io::Result<()> {
+        self.write_to(BufWriter::new(File::create(filename)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weight::NotNan;
+
+    fn write_to_string<W: Display>(graph: &AdjacencyList<W>) -> String {
+        let mut output = vec![];
+        graph.write_to(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_read_adjacencylist() {
+        let graph: AdjacencyList = AdjacencyList::read_adjacencylist("dijkstraData.txt").unwrap();
+        assert_eq!(graph.num_nodes(), 201);
+        assert!(matches!(
+            AdjacencyList::<usize>::read_adjacencylist("no_such_file.txt"),
+            Err(ParseError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_gaps_and_order() {
+        // Vertex 2 has no line, and vertex 5 is only mentioned as a destination.
+        let graph: AdjacencyList = "\n3 1,2 5,7\n\n1\t3,12  4,1\n4\n0 1,0\n".parse().unwrap();
+        assert_eq!(
+            graph,
+            AdjacencyList(vec![
+                vec![Edge {
+                    destination: 1,
+                    dist: 0
+                }],
+                vec![
+                    Edge {
+                        destination: 3,
+                        dist: 12
+                    },
+                    Edge {
+                        destination: 4,
+                        dist: 1
+                    }
+                ],
+                vec![],
+                vec![
+                    Edge {
+                        destination: 1,
+                        dist: 2
+                    },
+                    Edge {
+                        destination: 5,
+                        dist: 7
+                    }
+                ],
+                vec![],
+                vec![],
+            ])
+        );
+        assert_eq!("".parse::<AdjacencyList>().unwrap().num_nodes(), 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        match "1 2,30\n2 3,x".parse::<AdjacencyList>() {
+            Err(ParseError::InvalidNumber {
+                line: 2,
+                column: 2,
+                value,
+            }) => assert_eq!(value, "x"),
+            other => panic!("Expected an InvalidNumber error, got {:?}", other),
+        }
+        assert!(matches!(
+            "1 2,30\n-2 3,4".parse::<AdjacencyList>(),
+            Err(ParseError::InvalidNumber {
+                line: 2,
+                column: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            "1 2,30 3;12".parse::<AdjacencyList>(),
+            Err(ParseError::InvalidEdge {
+                line: 1,
+                column: 3,
+                ..
+            })
+        ));
+        assert!(matches!(
+            "1 2,30\n\n2 1,1\n1 3,3".parse::<AdjacencyList>(),
+            Err(ParseError::DuplicateVertex { line: 4, vertex: 1 })
+        ));
+        assert!(matches!(
+            "1 2,NaN".parse::<AdjacencyList<NotNan>>(),
+            Err(ParseError::InvalidNumber {
+                line: 1,
+                column: 2,
+                ..
+            })
+        ));
+        match "1 18446744073709551615,3".parse::<AdjacencyList>() {
+            Err(ParseError::VertexTooLarge {
+                line: 1,
+                column: 2,
+                vertex,
+            }) => assert_eq!(vertex, usize::MAX),
+            other => panic!("Expected a VertexTooLarge error, got {:?}", other),
+        }
+        assert!(matches!(
+            "2 1,1\n4000000000000 1,1".parse::<AdjacencyList>(),
+            Err(ParseError::VertexTooLarge {
+                line: 2,
+                column: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            format!("1 {},1", MAX_VERTEX + 1).parse::<AdjacencyList>(),
+            Err(ParseError::VertexTooLarge {
+                line: 1,
+                column: 2,
+                ..
+            })
+        ));
+        assert_eq!(
+            "1 2,30 3;12"
+                .parse::<AdjacencyList>()
+                .unwrap_err()
+                .to_string(),
+            "line 1, column 3: expected an edge like 'destination,dist', found '3;12'"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let graph: AdjacencyList = AdjacencyList::parse_adjacencylist("testfiles/test1.txt");
+        let written = write_to_string(&graph);
+        assert_eq!(written, "1\t2,30\t3,12\n2\t3,40\t1,10\n3\t1,2\n");
+        assert_eq!(written.parse::<AdjacencyList>().unwrap(), graph);
+
+        let graph: AdjacencyList<NotNan> = "1 2,0.5 3,1e300\n3 1,0\n".parse().unwrap();
+        assert_eq!(
+            write_to_string(&graph)
+                .parse::<AdjacencyList<NotNan>>()
+                .unwrap(),
+            graph
+        );
+
+        // Node 0 is only written when it has edges, so those aren't lost.
+        let graph: AdjacencyList = "0 1,4\n1 0,3\n".parse().unwrap();
+        let written = write_to_string(&graph);
+        assert_eq!(written, "0\t1,4\n1\t0,3\n");
+        assert_eq!(written.parse::<AdjacencyList>().unwrap(), graph);
+
+        let graph: AdjacencyList = AdjacencyList::parse_adjacencylist("dijkstraData.txt");
+        // The process id keeps test runs in different checkouts from sharing the file.
+        let filename =
+            std::env::temp_dir().join(format!("hw2_test_round_trip_{}.txt", std::process::id()));
+        let filename = filename.to_str().unwrap();
+        graph.save_adjacencylist(filename).unwrap();
+        let reloaded = AdjacencyList::read_adjacencylist(filename);
+        std::fs::remove_file(filename).unwrap();
+        assert_eq!(reloaded.unwrap(), graph);
+    }
+}
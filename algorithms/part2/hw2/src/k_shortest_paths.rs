@@ -0,0 +1,347 @@
+use crate::point_to_point::Route;
+use crate::weight::{Overflow, Weight};
+use crate::{overflow_on_path_to, AdjacencyList, Edge};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+impl<W: Weight> AdjacencyList<W> {
+    // Implements Yen's algorithm. Returns up to k loopless paths from source to target in order
+    // of increasing length, so the first is a shortest path and the rest are the best
+    // alternatives to it. Fewer than k routes are returned if there aren't that many loopless
+    // paths. Paths of equal length can come in any order. Fails if one of the k routes would
+    // be too long for W.
+    pub fn k_shortest_paths(
+        &self,
+        source: usize,
+        target: usize,
+        k: usize,
+    ) -> Result<Vec<Route<W>>, Overflow> {
+        let mut routes: Vec<Route<W>> = vec![];
+        if k == 0 {
+            return Ok(routes);
+        }
+        match self.shortest_path(source, target)? {
+            Some(route) => routes.push(route),
+            None => return Ok(routes),
+        }
+        // Paths that could come next, shortest first, and every path that's ever been queued
+        // so the same path isn't found twice.
+        let mut candidates: BinaryHeap<Reverse<(W, Vec<usize>)>> = BinaryHeap::new();
+        let mut seen: HashSet<Vec<usize>> = HashSet::from([routes[0].path.clone()]);
+        // The first path found that's too long for W. It's longer than every candidate, so it
+        // only matters once they've all been used up.
+        let mut overflow: Option<Overflow> = None;
+
+        while routes.len() < k {
+            let last_path = &routes[routes.len() - 1].path;
+            let mut root_dist = W::ZERO;
+            // Every new path follows last_path up to some spur node and then leaves it.
+            for (i, &spur_node) in last_path[..last_path.len() - 1].iter().enumerate() {
+                let root = &last_path[..=i];
+                // Leaving the way any earlier path with the same root left would just find
+                // that path again, and going back through the root would make a loop.
+                let removed_edges: HashSet<(usize, usize)> = routes
+                    .iter()
+                    .filter(|route| route.path.len() > i + 1 && route.path[..=i] == *root)
+                    .map(|route| (spur_node, route.path[i + 1]))
+                    .collect();
+                let mut removed_nodes = vec![false; self.num_nodes()];
+                for &node in &root[..i] {
+                    removed_nodes[node] = true;
+                }
+
+                match self.restricted_shortest_path(
+                    spur_node,
+                    target,
+                    &removed_nodes,
+                    &removed_edges,
+                ) {
+                    Ok(Some(spur)) => match root_dist.checked_add(spur.distance) {
+                        Some(distance) => {
+                            let mut path = root[..i].to_vec();
+                            path.extend(spur.path);
+                            if seen.insert(path.clone()) {
+                                candidates.push(Reverse((distance, path)));
+                            }
+                        }
+                        None => {
+                            overflow.get_or_insert(Overflow {
+                                from: spur_node,
+                                to: spur.path[1],
+                            });
+                        }
+                    },
+                    Ok(None) => {}
+                    Err(spur_overflow) => {
+                        overflow.get_or_insert(spur_overflow);
+                    }
+                }
+
+                // The root of the next spur node is one edge longer. last_path is a shortest
+                // path under some restrictions, so it takes the shortest of any parallel edges,
+                // and its length fits in W, so its roots do too.
+                let next_node = last_path[i + 1];
+                root_dist = root_dist
+                    .checked_add(self.edge_dist(spur_node, next_node))
+                    .ok_or(Overflow {
+                        from: spur_node,
+                        to: next_node,
+                    })?;
+            }
+
+            match (candidates.pop(), overflow) {
+                (Some(Reverse((distance, path))), _) => routes.push(Route { distance, path }),
+                (None, Some(overflow)) => return Err(overflow),
+                (None, None) => break,
+            }
+        }
+
+        Ok(routes)
+    }
+
+    // Like shortest_path, but as if every node i with removed_nodes[i] set, and every edge
+    // start -> destination in removed_edges, had been deleted from the graph.
+    fn restricted_shortest_path(
+        &self,
+        source: usize,
+        target: usize,
+        removed_nodes: &[bool],
+        removed_edges: &HashSet<(usize, usize)>,
+    ) -> Result<Option<Route<W>>, Overflow> {
+        let AdjacencyList(adjacencies) = self;
+        let mut distances: Vec<Option<W>> = vec![None; self.num_nodes()];
+        let mut predecessors: Vec<Option<usize>> = vec![None; self.num_nodes()];
+        let mut node_queue: BinaryHeap<Edge<W>> = BinaryHeap::new();
+        let mut overflows = vec![];
+
+        distances[source] = Some(W::ZERO);
+        node_queue.push(Edge {
+            destination: source,
+            dist: W::ZERO,
+        });
+
+        while let Some(Edge { destination, dist }) = node_queue.pop() {
+            if Some(dist) != distances[destination] {
+                continue;
+            }
+            if destination == target {
+                let mut path = vec![target];
+                while let Some(predecessor) = predecessors[*path.last().unwrap()] {
+                    path.push(predecessor);
+                }
+                path.reverse();
+                return Ok(Some(Route {
+                    distance: dist,
+                    path,
+                }));
+            }
+            for edge in &adjacencies[destination] {
+                if removed_nodes[edge.destination]
+                    || removed_edges.contains(&(destination, edge.destination))
+                {
+                    continue;
+                }
+                let Some(new_path_dist) = dist.checked_add(edge.dist) else {
+                    overflows.push(Overflow {
+                        from: destination,
+                        to: edge.destination,
+                    });
+                    continue;
+                };
+                if distances[edge.destination].is_none_or(|dist| new_path_dist < dist) {
+                    distances[edge.destination] = Some(new_path_dist);
+                    predecessors[edge.destination] = Some(destination);
+                    node_queue.push(Edge {
+                        destination: edge.destination,
+                        dist: new_path_dist,
+                    });
+                }
+            }
+        }
+
+        let follows = |start: usize, destination: usize| {
+            !removed_nodes[destination] && !removed_edges.contains(&(start, destination))
+        };
+        match overflow_on_path_to(adjacencies, &overflows, target, follows) {
+            Some(overflow) => Err(overflow),
+            None => Ok(None),
+        }
+    }
+
+    // Returns the length of the shortest edge from start to destination. Panics if there is no
+    // such edge.
+    fn edge_dist(&self, start: usize, destination: usize) -> W {
+        let AdjacencyList(adjacencies) = self;
+        adjacencies[start]
+            .iter()
+            .filter(|edge| edge.destination == destination)
+            .map(|edge| edge.dist)
+            .min()
+            .expect("Paths should only follow edges in the graph")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::XorShift;
+
+    fn route(distance: usize, path: Vec<usize>) -> Route {
+        Route { distance, path }
+    }
+
+    // Returns the lengths of every loopless path from source to target, sorted, by trying them
+    // all.
+    fn all_path_lengths(graph: &AdjacencyList, source: usize, target: usize) -> Vec<usize> {
+        let AdjacencyList(adjacencies) = graph;
+        let mut lengths = vec![];
+        let mut on_path = vec![false; graph.num_nodes()];
+        let mut todo = vec![(source, 0, false)];
+        // Entries with true mark where source..node is finished, so node can be used again.
+        while let Some((node, length, leaving)) = todo.pop() {
+            if leaving {
+                on_path[node] = false;
+                continue;
+            }
+            if node == target {
+                lengths.push(length);
+                continue;
+            }
+            on_path[node] = true;
+            todo.push((node, length, true));
+            for edge in &adjacencies[node] {
+                if !on_path[edge.destination] {
+                    todo.push((edge.destination, length + edge.dist, false));
+                }
+            }
+        }
+        lengths.sort_unstable();
+        lengths
+    }
+
+    #[test]
+    fn test_test1() {
+        let graph: AdjacencyList = AdjacencyList::parse_adjacencylist("testfiles/test1.txt");
+        assert_eq!(
+            graph.k_shortest_paths(2, 3, 5),
+            Ok(vec![route(22, vec![2, 1, 3]), route(40, vec![2, 3])])
+        );
+        assert_eq!(
+            graph.k_shortest_paths(1, 3, 1),
+            Ok(vec![route(12, vec![1, 3])])
+        );
+        assert_eq!(graph.k_shortest_paths(1, 1, 3), Ok(vec![route(0, vec![1])]));
+        assert_eq!(graph.k_shortest_paths(1, 0, 3), Ok(vec![]));
+        assert_eq!(graph.k_shortest_paths(1, 3, 0), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_yen() {
+        // The usual example for Yen's algorithm, with C, D, E, F, G, H numbered 1 to 6.
+        let graph: AdjacencyList = AdjacencyList::parse_adjacencylist("testfiles/yen.txt");
+        let routes = graph.k_shortest_paths(1, 6, 3).unwrap();
+        assert_eq!(
+            routes[..2],
+            [route(5, vec![1, 3, 4, 6]), route(7, vec![1, 3, 5, 6])]
+        );
+        assert_eq!(routes[2].distance, 8);
+
+        // There are seven loopless paths in all, three of them of length 8.
+        let routes = graph.k_shortest_paths(1, 6, 10).unwrap();
+        let lengths: Vec<usize> = routes.iter().map(|route| route.distance).collect();
+        assert_eq!(lengths, vec![5, 7, 8, 8, 8, 11, 11]);
+        let mut paths: Vec<&[usize]> = routes[2..5].iter().map(|route| &route.path[..]).collect();
+        paths.sort_unstable();
+        assert_eq!(
+            paths,
+            [&[1, 2, 4, 6][..], &[1, 3, 2, 4, 6], &[1, 3, 4, 5, 6]]
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let graph: AdjacencyList<u8> = AdjacencyList(vec![
+            vec![],
+            vec![
+                Edge {
+                    destination: 2,
+                    dist: 200,
+                },
+                Edge {
+                    destination: 3,
+                    dist: 50,
+                },
+            ],
+            vec![Edge {
+                destination: 3,
+                dist: 100,
+            }],
+            vec![],
+        ]);
+        assert_eq!(graph.k_shortest_paths(1, 3, 1).unwrap()[0].distance, 50);
+        assert_eq!(
+            graph.k_shortest_paths(1, 3, 2),
+            Err(Overflow { from: 2, to: 3 })
+        );
+    }
+
+    #[test]
+    fn test_overflow_after_shorter_routes() {
+        // 1 -> 2 -> 3 overflows a u8, but the first two routes fit.
+        let graph: AdjacencyList<u8> = "1 2,200 3,5 4,210\n2 3,100\n3\n4 3,10\n".parse().unwrap();
+        let expected = vec![
+            Route {
+                distance: 5,
+                path: vec![1, 3],
+            },
+            Route {
+                distance: 220,
+                path: vec![1, 4, 3],
+            },
+        ];
+        assert_eq!(graph.k_shortest_paths(1, 3, 2), Ok(expected));
+        assert_eq!(
+            graph.k_shortest_paths(1, 3, 3),
+            Err(Overflow { from: 2, to: 3 })
+        );
+    }
+
+    #[test]
+    fn test_matches_brute_force_on_random_graphs() {
+        let mut rng = XorShift::new(0x2545_f491_4f6c_dd1d);
+        let mut next = |n: usize| rng.below(n);
+
+        for _ in 0..30 {
+            let num_nodes = 8;
+            let mut adjacencies: Vec<Vec<Edge>> = (0..=num_nodes).map(|_| vec![]).collect();
+            for _ in 0..next(25) {
+                let (start, destination) = (next(num_nodes) + 1, next(num_nodes) + 1);
+                // Parallel edges would make several paths with the same nodes.
+                if adjacencies[start]
+                    .iter()
+                    .all(|edge| edge.destination != destination)
+                {
+                    adjacencies[start].push(Edge {
+                        destination,
+                        dist: next(10) + 1,
+                    });
+                }
+            }
+            let graph = AdjacencyList(adjacencies);
+            let (source, target) = (next(num_nodes) + 1, next(num_nodes) + 1);
+
+            let lengths = all_path_lengths(&graph, source, target);
+            let routes = graph.k_shortest_paths(source, target, 20).unwrap();
+            let found: Vec<usize> = routes.iter().map(|route| route.distance).collect();
+            assert_eq!(found, lengths[..lengths.len().min(20)]);
+            let paths: HashSet<&Vec<usize>> = routes.iter().map(|route| &route.path).collect();
+            assert_eq!(paths.len(), routes.len());
+            for route in &routes {
+                let mut nodes = route.path.clone();
+                nodes.sort_unstable();
+                nodes.dedup();
+                assert_eq!(nodes.len(), route.path.len());
+            }
+        }
+    }
+}
@@ -1,105 +1,240 @@
-use std::{fs, collections::BinaryHeap};
+pub mod format;
+pub mod k_shortest_paths;
+pub mod multi_source;
+pub mod point_to_point;
+pub mod weight;
 
+use indexed_heap::IndexedHeap;
+use std::{collections::BinaryHeap, str::FromStr};
+use weight::{Overflow, Weight};
+
+// An edge to destination with weight dist. Weights can be any Weight, e.g. u32 travel times or
+// NotNan probabilities, and are usize unless stated otherwise.
 #[derive(Debug, PartialEq, Eq)]
-pub struct Edge {
+pub struct Edge<W = usize> {
     destination: usize,
-    dist: usize,
+    dist: W,
 }
 
 
-impl Edge {
-    fn from_str(data_string: &str) -> Edge {
-        let edge_data: Vec<usize> = data_string
-            .split(",")
-            .map(|s|
-                s.parse().expect("Expected a string of the form: 'destination,dist'"))
-            .collect();
-        
-        Edge { destination: edge_data[0], dist: edge_data[1] }
+// Only the tests still parse single edges this way; the adjacency list reader reports errors
+// instead of panicking.
+#[cfg(test)]
+impl<W: FromStr> Edge<W> {
+    fn from_str(data_string: &str) -> Edge<W> {
+        const FORMAT: &str = "Expected a string of the form: 'destination,dist'";
+        let (destination, dist) = data_string.split_once(',').expect(FORMAT);
+
+        Edge {
+            destination: destination.parse().expect(FORMAT),
+            dist: dist.parse().ok().expect(FORMAT),
+        }
     }
 }
 
-impl Ord for Edge {
+impl<W: Ord> Ord for Edge<W> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         other.dist.cmp(&self.dist)
             .then_with(|| other.destination.cmp(&self.destination))
     }
 }
 
-impl PartialOrd for Edge {
+impl<W: Ord> PartialOrd for Edge<W> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
 
 #[derive(Debug, PartialEq)]
-pub struct AdjacencyList(Vec<Vec<Edge>>);
-
-impl AdjacencyList {
-
-    pub fn parse_adjacencylist(filename: &str) -> AdjacencyList {
-        let file_data = fs::read_to_string(filename)
-            .expect("Couldn't read file.");
-    
-        let mut adj_list: Vec<Vec<Edge>> = vec![vec![]];
-        for line in file_data.lines() {
-            let mut split_line = line.split_ascii_whitespace();
-            let mut edges = vec![];
-            split_line.next();
-            for edge in split_line {
-                edges.push(Edge::from_str(edge))
-            }
-            adj_list.push(edges)
-        }
-    
-        AdjacencyList(adj_list)
+pub struct AdjacencyList<W = usize>(Vec<Vec<Edge<W>>>);
+
+impl<W: FromStr> AdjacencyList<W> {
+
+    // Like read_adjacencylist, but panics if the file can't be read or parsed.
+    pub fn parse_adjacencylist(filename: &str) -> AdjacencyList<W> {
+        AdjacencyList::read_adjacencylist(filename)
+            .unwrap_or_else(|error| panic!("Couldn't parse {}: {}", filename, error))
     }
+}
+
+impl<W: Weight> AdjacencyList<W> {
 
     pub fn num_nodes(&self) -> usize {
         let AdjacencyList(adj_list ) = &self;
         adj_list.len()
     }
 
-    // Implements Dijkstra's algorithm. Returns a vector of distances, where
-    // result[i] is the distance from the source node to node i. Distance is MAX_DIST
-    // if node is not reachable from source
-    pub fn dijkstra(&self, source: usize) -> Vec<usize> {
-        const MAX_DIST: usize = 1_000_000;
-        let mut distances = vec![MAX_DIST; self.num_nodes()];
-        let mut node_queue: BinaryHeap<Edge> = BinaryHeap::new();
+    // Implements Dijkstra's algorithm. Returns the ShortestPaths from source to every other
+    // node, which holds both the distances and the shortest paths themselves, or an Overflow
+    // error if some node can be reached but every path to it is too long for W.
+    pub fn dijkstra(&self, source: usize) -> Result<ShortestPaths<W>, Overflow> {
+        self.dijkstra_with_max_queue_len(source).map(|(paths, _)| paths)
+    }
+
+    // Same as dijkstra, but also returns the most entries the queue held at once, so its size
+    // can be compared with dijkstra_indexed's.
+    pub fn dijkstra_with_max_queue_len(&self, source: usize)
+        -> Result<(ShortestPaths<W>, usize), Overflow> {
+        let mut distances = vec![None; self.num_nodes()];
+        let mut predecessors = vec![None; self.num_nodes()];
+        let mut node_queue: BinaryHeap<Edge<W>> = BinaryHeap::new();
+        let mut overflows = vec![];
 
         let AdjacencyList(adjacencies) = self;
 
-        for edge in &adjacencies[source] {
-            node_queue.push(Edge{ destination: edge.destination, dist: edge.dist })
-        }
+        distances[source] = Some(W::ZERO);
+        node_queue.push(Edge { destination: source, dist: W::ZERO });
+        // Nodes are only pushed by update_distances, so the queue is largest right after a call.
+        let mut max_queue_len = node_queue.len();
 
         while let Some(Edge { destination, dist }) = node_queue.pop() {
-            if dist < distances[destination] {
-                distances[destination] = dist;
-                update_distances(adjacencies, &mut node_queue, &mut distances, destination,
-                    dist)   
+            // Nodes are pushed again whenever we find a shorter path to them, so skip the
+            // entries for paths that have since been beaten.
+            if Some(dist) == distances[destination] {
+                update_distances(adjacencies, &mut node_queue, &mut distances,
+                    &mut predecessors, &mut overflows, destination, dist);
+                max_queue_len = max_queue_len.max(node_queue.len());
             }
         }
 
-        distances
+        if let Some(overflow) = first_unreached(&overflows, &distances) {
+            return Err(overflow);
+        }
+        Ok((ShortestPaths { source, distances, predecessors }, max_queue_len))
     }
 
+    // Same as dijkstra, but keeps the queue in an IndexedHeap and lowers the key of a node
+    // when a shorter path to it is found, instead of pushing it again. The queue never holds
+    // more than one entry per node.
+    pub fn dijkstra_indexed(&self, source: usize) -> Result<ShortestPaths<W>, Overflow> {
+        self.dijkstra_indexed_with_max_queue_len(source).map(|(paths, _)| paths)
+    }
+
+    // Same as dijkstra_indexed, but also returns the most nodes the queue held at once.
+    pub fn dijkstra_indexed_with_max_queue_len(&self, source: usize)
+        -> Result<(ShortestPaths<W>, usize), Overflow> {
+        let mut distances: Vec<Option<W>> = vec![None; self.num_nodes()];
+        let mut predecessors = vec![None; self.num_nodes()];
+        let mut node_queue: IndexedHeap<W> = IndexedHeap::new(self.num_nodes());
+        let mut overflows = vec![];
+
+        let AdjacencyList(adjacencies) = self;
 
+        distances[source] = Some(W::ZERO);
+        node_queue.push(source, W::ZERO);
+        let mut max_queue_len = node_queue.len();
+
+        while let Some((start, path_dist)) = node_queue.pop() {
+            for edge in &adjacencies[start] {
+                let Some(new_path_dist) = path_dist.checked_add(edge.dist) else {
+                    overflows.push(Overflow { from: start, to: edge.destination });
+                    continue;
+                };
+                if distances[edge.destination].is_none_or(|dist| new_path_dist < dist) {
+                    distances[edge.destination] = Some(new_path_dist);
+                    predecessors[edge.destination] = Some(start);
+                    node_queue.push_or_decrease(edge.destination, new_path_dist);
+                }
+            }
+            max_queue_len = max_queue_len.max(node_queue.len());
+        }
+
+        if let Some(overflow) = first_unreached(&overflows, &distances) {
+            return Err(overflow);
+        }
+        Ok((ShortestPaths { source, distances, predecessors }, max_queue_len))
+    }
+
+    // Returns the number of edges in the graph.
+    pub fn num_edges(&self) -> usize {
+        let AdjacencyList(adj_list) = self;
+        adj_list.iter().map(Vec::len).sum()
+    }
+
+
+}
+
+// The result of running Dijkstra's algorithm from source.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ShortestPaths<W = usize> {
+    pub source: usize,
+    // distances[i] is the distance from source to node i, or None if node i is not
+    // reachable from source.
+    pub distances: Vec<Option<W>>,
+    // predecessors[i] is the node before node i on a shortest path from source, or None if
+    // node i is source or not reachable from it.
+    pub predecessors: Vec<Option<usize>>,
+}
+
+impl<W> ShortestPaths<W> {
+    // Returns a shortest path from source to target, starting with source and ending with
+    // target, or None if target is not reachable from source.
+    pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+        self.distances.get(target)?.as_ref()?;
+        let mut path = vec![target];
+        let mut node = target;
+        while let Some(predecessor) = self.predecessors[node] {
+            path.push(predecessor);
+            node = predecessor;
+        }
+        path.reverse();
+        Some(path)
+    }
 }
 
-fn update_distances(adjacencies: &Vec<Vec<Edge>>, node_queue: &mut BinaryHeap<Edge>, 
-    distances: &mut Vec<usize>, start: usize, path_dist: usize) {
+// Relaxes every edge leaving start, which is path_dist away from the source, pushing each
+// node we find a shorter path to onto node_queue. An edge that makes the path too long for W
+// can't be on a shortest path to anywhere we can represent, so it's skipped and added to
+// overflows instead.
+fn update_distances<W: Weight>(adjacencies: &[Vec<Edge<W>>], node_queue: &mut BinaryHeap<Edge<W>>,
+    distances: &mut [Option<W>], predecessors: &mut [Option<usize>],
+    overflows: &mut Vec<Overflow>, start: usize, path_dist: W) {
 
     for edge in &adjacencies[start] {
-        let new_path_dist = edge.dist + path_dist;
-        if new_path_dist < distances[edge.destination] {
-            //distances[edge.destination] = new_path_dist;
+        let Some(new_path_dist) = path_dist.checked_add(edge.dist) else {
+            overflows.push(Overflow { from: start, to: edge.destination });
+            continue;
+        };
+        if distances[edge.destination].is_none_or(|dist| new_path_dist < dist) {
+            distances[edge.destination] = Some(new_path_dist);
+            predecessors[edge.destination] = Some(start);
             node_queue.push(Edge { destination: edge.destination, dist: new_path_dist })
         }
     }
 }
 
+// Returns the first of the edges a finished search skipped for overflowing that leads to a node
+// the search never reached. If some node can be reached, but only by paths too long for W,
+// there is always such an edge on the way to it.
+fn first_unreached<W>(overflows: &[Overflow], distances: &[Option<W>]) -> Option<Overflow> {
+    overflows.iter().copied().find(|overflow| distances[overflow.to].is_none())
+}
+
+// Returns the first of the edges a search skipped for overflowing from which target can be
+// reached, only going along edges start -> destination that follows(start, destination)
+// allows. Once the search has run out of nodes without reaching target, this tells apart a
+// target that can't be reached at all from one that's only reached by paths too long for W.
+fn overflow_on_path_to<W>(adjacencies: &[Vec<Edge<W>>], overflows: &[Overflow], target: usize,
+    follows: impl Fn(usize, usize) -> bool) -> Option<Overflow> {
+
+    let mut visited = vec![false; adjacencies.len()];
+    for &overflow in overflows {
+        let mut todo = vec![overflow.to];
+        while let Some(node) = todo.pop() {
+            if node == target {
+                return Some(overflow);
+            }
+            if !visited[node] {
+                visited[node] = true;
+                todo.extend(adjacencies[node].iter()
+                    .map(|edge| edge.destination)
+                    .filter(|&destination| follows(node, destination)));
+            }
+        }
+    }
+    None
+}
+
 
 
 
@@ -107,6 +242,7 @@ fn update_distances(adjacencies: &Vec<Vec<Edge>>, node_queue: &mut BinaryHeap<Ed
 #[cfg(test)]
 mod tests {
     use crate::{AdjacencyList, Edge};
+    use crate::weight::{NotNan, Overflow};
 
     fn init_list1 () -> AdjacencyList {
         let adjacencies = vec![
@@ -128,9 +264,99 @@ mod tests {
     #[test]
     fn test_dijkstra() {
         let graph = init_list1();
-        assert_eq!(graph.dijkstra(1), vec![1_000_000, 14, 30, 12]);
-        assert_eq!(graph.dijkstra(2), vec![1_000_000, 10, 40, 22]);
-        assert_eq!(graph.dijkstra(3), vec![1_000_000, 2, 32, 14]);
+        assert_eq!(graph.dijkstra(1).unwrap().distances, vec![None, Some(0), Some(30), Some(12)]);
+        assert_eq!(graph.dijkstra(2).unwrap().distances, vec![None, Some(10), Some(0), Some(22)]);
+        assert_eq!(graph.dijkstra(3).unwrap().distances, vec![None, Some(2), Some(32), Some(0)]);
+        assert_eq!(graph.dijkstra(3).unwrap().predecessors, vec![None, Some(3), Some(1), None]);
+    }
+
+    #[test]
+    fn test_dijkstra_indexed() {
+        let graph = init_list1();
+        for source in 1..graph.num_nodes() {
+            assert_eq!(graph.dijkstra_indexed(source), graph.dijkstra(source));
+        }
+
+        let graph: AdjacencyList = AdjacencyList::parse_adjacencylist("dijkstraData.txt");
+        assert_eq!(graph.num_edges(), 3734);
+        for source in [1, 50, 200] {
+            assert_eq!(graph.dijkstra_indexed(source).unwrap().distances, graph.dijkstra(source).unwrap().distances);
+        }
+
+        // The indexed heap never holds more than one entry per node.
+        let (_, lazy_len) = graph.dijkstra_with_max_queue_len(1).unwrap();
+        let (_, indexed_len) = graph.dijkstra_indexed_with_max_queue_len(1).unwrap();
+        assert!(indexed_len < graph.num_nodes() && indexed_len <= lazy_len);
+    }
+
+    #[test]
+    fn test_path_to() {
+        let graph = init_list1();
+        let paths = graph.dijkstra(2).unwrap();
+        assert_eq!(paths.path_to(3), Some(vec![2, 1, 3]));
+        assert_eq!(paths.path_to(1), Some(vec![2, 1]));
+        assert_eq!(paths.path_to(2), Some(vec![2]));
+        assert_eq!(paths.path_to(0), None);
+        assert_eq!(paths.path_to(4), None);
+    }
+
+    #[test]
+    fn test_distances_past_a_million() {
+        // Node 3 can't be reached, and node 2 is further away than the old MAX_DIST.
+        let graph: AdjacencyList = AdjacencyList(vec![
+            vec![],
+            vec![Edge {destination: 2, dist: 3_000_000}],
+            vec![],
+            vec![Edge {destination: 1, dist: 1}],
+        ]);
+        let paths = graph.dijkstra(1).unwrap();
+        assert_eq!(paths.distances, vec![None, Some(0), Some(3_000_000), None]);
+        assert_eq!(paths.path_to(2), Some(vec![1, 2]));
+        assert_eq!(paths.path_to(3), None);
+    }
+
+    #[test]
+    fn test_other_weights() {
+        let graph: AdjacencyList<u32> = AdjacencyList::parse_adjacencylist("testfiles/test1.txt");
+        assert_eq!(graph.dijkstra(3).unwrap().distances, vec![None, Some(2), Some(32), Some(0)]);
+
+        let weight = |value| NotNan::new(value).unwrap();
+        let graph = AdjacencyList(vec![
+            vec![],
+            vec![Edge {destination: 2, dist: weight(0.5)}, Edge {destination: 3, dist: weight(2.0)}],
+            vec![Edge {destination: 3, dist: weight(0.25)}],
+            vec![],
+        ]);
+        let paths = graph.dijkstra(1).unwrap();
+        assert_eq!(paths.distances, vec![None, Some(weight(0.0)), Some(weight(0.5)), Some(weight(0.75))]);
+        assert_eq!(paths.path_to(3), Some(vec![1, 2, 3]));
+        assert_eq!(graph.dijkstra_indexed(1), Ok(paths));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // 1 -> 2 -> 3 adds up to more than u8::MAX.
+        let graph: AdjacencyList<u8> = AdjacencyList(vec![
+            vec![],
+            vec![Edge {destination: 2, dist: 200}],
+            vec![Edge {destination: 3, dist: 100}],
+            vec![],
+        ]);
+        assert_eq!(graph.dijkstra(1), Err(Overflow { from: 2, to: 3 }));
+        assert_eq!(graph.dijkstra_indexed(1), Err(Overflow { from: 2, to: 3 }));
+        assert_eq!(graph.dijkstra(2).unwrap().distances, vec![None, None, Some(0), Some(100)]);
+        assert_eq!(Overflow { from: 2, to: 3 }.to_string(),
+            "path length overflowed following the edge 2 -> 3");
+    }
+
+    #[test]
+    fn test_overflow_beside_shorter_path() {
+        // 1 -> 2 -> 3 overflows a u8, but 1 -> 3 is shorter anyway, so every distance fits.
+        let graph: AdjacencyList<u8> = "1 2,200 3,5\n2 3,100\n3\n".parse().unwrap();
+        let distances = vec![None, Some(0), Some(200), Some(5)];
+        assert_eq!(graph.dijkstra(1).unwrap().distances, distances);
+        assert_eq!(graph.dijkstra_indexed(1).unwrap().distances, distances);
+        assert_eq!(graph.dijkstra(1).unwrap().path_to(3), Some(vec![1, 3]));
     }
 
     #[test]
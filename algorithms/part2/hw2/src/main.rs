@@ -1,11 +1,13 @@
 use hw2::*;
 fn main() {
-    let adj_list = AdjacencyList::parse_adjacencylist("dijkstraData.txt");
+    let adj_list: AdjacencyList = AdjacencyList::read_adjacencylist("dijkstraData.txt")
+        .expect("Couldn't parse the adjacency list");
     let indices_of_interest = vec![7,37,59,82,99,115,133,165,188,197];
-    let shortest_distances = adj_list.dijkstra(1);
+    let shortest_paths = adj_list.dijkstra(1).expect("Distances should fit in a usize");
     let mut result = vec![];
     for i in indices_of_interest {
-        result.push(shortest_distances[i])
+        // The assignment reports unreachable nodes as being 1000000 away.
+        result.push(shortest_paths.distances[i].unwrap_or(1_000_000))
     }
     println!("{:?}", result)
 }
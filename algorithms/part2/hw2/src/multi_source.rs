@@ -0,0 +1,224 @@
+use crate::weight::{Overflow, Weight};
+use crate::{first_unreached, AdjacencyList};
+use indexed_heap::IndexedHeap;
+
+// The result of running Dijkstra's algorithm from several sources at once.
+#[derive(Debug, PartialEq, Eq)]
+pub struct NearestSources<W = usize> {
+    // distances[i] is the distance from the nearest source to node i, counting that source's
+    // offset, or None if node i isn't reachable from any source.
+    pub distances: Vec<Option<W>>,
+    // nearest[i] is the source distances[i] is measured from. If several sources are equally
+    // near, this is the smallest of them.
+    pub nearest: Vec<Option<usize>>,
+}
+
+impl<W> NearestSources<W> {
+    // Splits the nodes up by their nearest source, i.e. into the cells of the graph's Voronoi
+    // diagram. Returns each source with the nodes nearest to it, in order of source, leaving
+    // out unreachable nodes and sources that no node is nearest to.
+    pub fn cells(&self) -> Vec<(usize, Vec<usize>)> {
+        let mut cells: Vec<(usize, Vec<usize>)> = vec![];
+        let mut nodes: Vec<(usize, usize)> = self
+            .nearest
+            .iter()
+            .enumerate()
+            .filter_map(|(node, &source)| Some((source?, node)))
+            .collect();
+        nodes.sort_unstable();
+        for (source, node) in nodes {
+            match cells.last_mut() {
+                Some((last_source, cell)) if *last_source == source => cell.push(node),
+                _ => cells.push((source, vec![node])),
+            }
+        }
+        cells
+    }
+}
+
+impl<W: Weight> AdjacencyList<W> {
+    // Finds the nearest of sources to every node, and how far away it is. This is the same as
+    // running dijkstra from each source and taking the smallest distance for every node, but
+    // only searches the graph once.
+    pub fn nearest_sources(&self, sources: &[usize]) -> Result<NearestSources<W>, Overflow> {
+        let sources: Vec<(usize, W)> = sources.iter().map(|&source| (source, W::ZERO)).collect();
+        self.nearest_sources_with_offsets(&sources)
+    }
+
+    // Like nearest_sources, but each source comes with an offset that's added to the distance
+    // of every path from it, e.g. the cost of using that source at all. Fails if some node can
+    // be reached, but every path to it from a source is too long for W.
+    pub fn nearest_sources_with_offsets(
+        &self,
+        sources: &[(usize, W)],
+    ) -> Result<NearestSources<W>, Overflow> {
+        let mut distances: Vec<Option<W>> = vec![None; self.num_nodes()];
+        let mut nearest: Vec<Option<usize>> = vec![None; self.num_nodes()];
+        // Nodes are keyed on their distance and then their source, so ties go to the smallest
+        // source.
+        let mut node_queue: IndexedHeap<(W, usize)> = IndexedHeap::new(self.num_nodes());
+        let mut overflows = vec![];
+
+        let AdjacencyList(adjacencies) = self;
+
+        for &(source, offset) in sources {
+            if node_queue.push_or_decrease(source, (offset, source)) {
+                distances[source] = Some(offset);
+                nearest[source] = Some(source);
+            }
+        }
+
+        while let Some((start, (path_dist, source))) = node_queue.pop() {
+            for edge in &adjacencies[start] {
+                // Like in dijkstra, a path that overflows can't be the shortest to anywhere.
+                let Some(new_path_dist) = path_dist.checked_add(edge.dist) else {
+                    overflows.push(Overflow {
+                        from: start,
+                        to: edge.destination,
+                    });
+                    continue;
+                };
+                let key = (new_path_dist, source);
+                let current = distances[edge.destination].zip(nearest[edge.destination]);
+                // Settled nodes are popped in order of key, so they're never beaten here.
+                if current.is_none_or(|current| key < current) {
+                    distances[edge.destination] = Some(new_path_dist);
+                    nearest[edge.destination] = Some(source);
+                    node_queue.push_or_decrease(edge.destination, key);
+                }
+            }
+        }
+
+        if let Some(overflow) = first_unreached(&overflows, &distances) {
+            return Err(overflow);
+        }
+        Ok(NearestSources { distances, nearest })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Edge;
+    use xorshift::XorShift;
+
+    #[test]
+    fn test_nearest_sources() {
+        let graph: AdjacencyList = AdjacencyList::parse_adjacencylist("testfiles/test1.txt");
+        let result = graph.nearest_sources(&[2, 3]).unwrap();
+        assert_eq!(result.distances, vec![None, Some(2), Some(0), Some(0)]);
+        assert_eq!(result.nearest, vec![None, Some(3), Some(2), Some(3)]);
+        assert_eq!(result.cells(), vec![(2, vec![2]), (3, vec![1, 3])]);
+
+        // A single source is just dijkstra.
+        let result = graph.nearest_sources(&[1]).unwrap();
+        assert_eq!(result.distances, graph.dijkstra(1).unwrap().distances);
+        assert_eq!(result.cells(), vec![(1, vec![1, 2, 3])]);
+
+        let result = graph.nearest_sources(&[]).unwrap();
+        assert_eq!(result.distances, vec![None; 4]);
+        assert_eq!(result.cells(), vec![]);
+    }
+
+    #[test]
+    fn test_offsets() {
+        let graph: AdjacencyList = AdjacencyList::parse_adjacencylist("testfiles/test1.txt");
+        // Starting at 3 costs 20, so 1 is nearer to 2, but 3 is still nearer to itself.
+        let result = graph
+            .nearest_sources_with_offsets(&[(2, 0), (3, 20)])
+            .unwrap();
+        assert_eq!(result.distances, vec![None, Some(10), Some(0), Some(20)]);
+        assert_eq!(result.nearest, vec![None, Some(2), Some(2), Some(3)]);
+
+        // Starting at 3 costs so much that it's cheaper to get there from 2, and the cheaper
+        // of two offsets for the same source is used.
+        let result = graph
+            .nearest_sources_with_offsets(&[(3, 50), (2, 0), (3, 30)])
+            .unwrap();
+        assert_eq!(result.distances, vec![None, Some(10), Some(0), Some(22)]);
+        assert_eq!(result.nearest, vec![None, Some(2), Some(2), Some(2)]);
+        assert_eq!(result.cells(), vec![(2, vec![1, 2, 3])]);
+    }
+
+    #[test]
+    fn test_ties() {
+        // 2 is 5 away from both 1 and 3.
+        let graph: AdjacencyList = AdjacencyList(vec![
+            vec![],
+            vec![Edge {
+                destination: 2,
+                dist: 5,
+            }],
+            vec![],
+            vec![Edge {
+                destination: 2,
+                dist: 5,
+            }],
+        ]);
+        for sources in [[1, 3], [3, 1]] {
+            let result = graph.nearest_sources(&sources).unwrap();
+            assert_eq!(result.nearest, vec![None, Some(1), Some(1), Some(3)]);
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let graph: AdjacencyList<u8> = AdjacencyList(vec![
+            vec![],
+            vec![Edge {
+                destination: 2,
+                dist: 100,
+            }],
+            vec![],
+        ]);
+        assert_eq!(
+            graph.nearest_sources_with_offsets(&[(1, 200)]),
+            Err(Overflow { from: 1, to: 2 })
+        );
+        assert!(graph.nearest_sources_with_offsets(&[(1, 100)]).is_ok());
+
+        // 2 is too far from 1, but 3 is near enough to it.
+        let graph: AdjacencyList<u8> = "1 2,100\n3 2,50\n".parse().unwrap();
+        let result = graph
+            .nearest_sources_with_offsets(&[(1, 200), (3, 0)])
+            .unwrap();
+        assert_eq!(result.distances, vec![None, Some(200), Some(50), Some(0)]);
+        assert_eq!(result.nearest, vec![None, Some(1), Some(3), Some(3)]);
+    }
+
+    #[test]
+    fn test_matches_dijkstra_from_every_source() {
+        let mut rng = XorShift::new(0x2545_f491_4f6c_dd1d);
+        let mut next = |n: usize| rng.below(n);
+
+        for _ in 0..30 {
+            let num_nodes = 20;
+            let mut adjacencies: Vec<Vec<Edge>> = (0..=num_nodes).map(|_| vec![]).collect();
+            for _ in 0..next(60) {
+                let (start, destination) = (next(num_nodes) + 1, next(num_nodes) + 1);
+                adjacencies[start].push(Edge {
+                    destination,
+                    dist: next(10),
+                });
+            }
+            let graph = AdjacencyList(adjacencies);
+            let sources: Vec<(usize, usize)> = (0..next(5))
+                .map(|_| (next(num_nodes) + 1, next(15)))
+                .collect();
+            let result = graph.nearest_sources_with_offsets(&sources).unwrap();
+
+            for node in 0..graph.num_nodes() {
+                // The smallest distance to node, and the smallest source with it.
+                let expected = sources
+                    .iter()
+                    .filter_map(|&(source, offset)| {
+                        let dist = graph.dijkstra(source).unwrap().distances[node]?;
+                        Some((dist + offset, source))
+                    })
+                    .min();
+                assert_eq!(result.distances[node], expected.map(|(dist, _)| dist));
+                assert_eq!(result.nearest[node], expected.map(|(_, source)| source));
+            }
+        }
+    }
+}
@@ -0,0 +1,560 @@
+use crate::weight::{NotNan, Overflow, Weight};
+use crate::{overflow_on_path_to, update_distances, AdjacencyList, Edge};
+use std::collections::BinaryHeap;
+
+// A shortest path between two nodes along with its length.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Route<W = usize> {
+    pub distance: W,
+    // The nodes on the path, starting with the source and ending with the target.
+    pub path: Vec<usize>,
+}
+
+// Estimates the distance from node to target for A*. The estimate must be admissible, i.e.
+// never more than the real distance, or A* can return paths that aren't the shortest.
+pub trait Heuristic<W = usize> {
+    fn estimate(&self, node: usize, target: usize) -> W;
+}
+
+// Any closure taking the node and the target can be used as a heuristic, e.g. |_, _| 0 to
+// turn A* back into Dijkstra's algorithm.
+impl<W, F: Fn(usize, usize) -> W> Heuristic<W> for F {
+    fn estimate(&self, node: usize, target: usize) -> W {
+        self(node, target)
+    }
+}
+
+// Uses the straight-line distance between the coordinates of two nodes, rounded down for
+// integer weights. This is admissible as long as no edge is shorter than the straight line
+// between its ends.
+#[derive(Debug, PartialEq)]
+pub struct EuclideanDistance {
+    // coordinates[i] is the position of node i.
+    pub coordinates: Vec<(f64, f64)>,
+}
+
+impl EuclideanDistance {
+    fn distance(&self, node: usize, target: usize) -> f64 {
+        let (x1, y1) = self.coordinates[node];
+        let (x2, y2) = self.coordinates[target];
+        (x1 - x2).hypot(y1 - y2)
+    }
+}
+
+impl Heuristic<usize> for EuclideanDistance {
+    fn estimate(&self, node: usize, target: usize) -> usize {
+        self.distance(node, target) as usize
+    }
+}
+
+impl Heuristic<NotNan> for EuclideanDistance {
+    fn estimate(&self, node: usize, target: usize) -> NotNan {
+        NotNan::new(self.distance(node, target)).expect("Coordinates shouldn't be NaN")
+    }
+}
+
+// The state of Dijkstra's algorithm searching out from a single source.
+struct Search<W> {
+    distances: Vec<Option<W>>,
+    predecessors: Vec<Option<usize>>,
+    node_queue: BinaryHeap<Edge<W>>,
+    // The edges that were skipped because following them overflowed.
+    overflows: Vec<Overflow>,
+}
+
+impl<W: Weight> Search<W> {
+    fn new(num_nodes: usize, source: usize) -> Search<W> {
+        let mut distances = vec![None; num_nodes];
+        distances[source] = Some(W::ZERO);
+        Search {
+            distances,
+            predecessors: vec![None; num_nodes],
+            node_queue: BinaryHeap::from([Edge {
+                destination: source,
+                dist: W::ZERO,
+            }]),
+            overflows: vec![],
+        }
+    }
+
+    // Returns the next node settle_next would settle and its distance, or None if every
+    // reachable node has been settled.
+    fn peek_next(&mut self) -> Option<(usize, W)> {
+        while let Some(&Edge { destination, dist }) = self.node_queue.peek() {
+            if Some(dist) == self.distances[destination] {
+                return Some((destination, dist));
+            }
+            // This entry is for a path that has since been beaten.
+            self.node_queue.pop();
+        }
+        None
+    }
+
+    // Settles the closest node not settled yet, relaxing the edges leaving it, and returns
+    // it.
+    fn settle_next(&mut self, adjacencies: &[Vec<Edge<W>>]) -> Option<usize> {
+        let (_, dist) = self.peek_next()?;
+        let Edge { destination, .. } = self
+            .node_queue
+            .pop()
+            .expect("peek_next found a node to settle");
+        update_distances(
+            adjacencies,
+            &mut self.node_queue,
+            &mut self.distances,
+            &mut self.predecessors,
+            &mut self.overflows,
+            destination,
+            dist,
+        );
+        Some(destination)
+    }
+
+    // Returns the path from the source to node by following predecessors, starting with the
+    // source.
+    fn path_to(&self, node: usize) -> Vec<usize> {
+        let mut path = vec![node];
+        let mut node = node;
+        while let Some(predecessor) = self.predecessors[node] {
+            path.push(predecessor);
+            node = predecessor;
+        }
+        path.reverse();
+        path
+    }
+}
+
+impl<W: Weight> AdjacencyList<W> {
+    // Returns the graph with every edge reversed.
+    pub fn reversed(&self) -> AdjacencyList<W> {
+        let AdjacencyList(adjacencies) = self;
+        let mut reversed: Vec<Vec<Edge<W>>> = (0..adjacencies.len()).map(|_| vec![]).collect();
+        for (start, edges) in adjacencies.iter().enumerate() {
+            for edge in edges {
+                reversed[edge.destination].push(Edge {
+                    destination: start,
+                    dist: edge.dist,
+                });
+            }
+        }
+        AdjacencyList(reversed)
+    }
+
+    // Returns a shortest path from source to target, or None if target isn't reachable. Runs
+    // Dijkstra's algorithm but stops as soon as target is settled, so nodes further away than
+    // target are never looked at. Fails only if target can be reached, but every path to it
+    // is too long for W.
+    pub fn shortest_path(
+        &self,
+        source: usize,
+        target: usize,
+    ) -> Result<Option<Route<W>>, Overflow> {
+        let AdjacencyList(adjacencies) = self;
+        let mut search = Search::new(self.num_nodes(), source);
+        // Stop before relaxing the edges leaving target, since we don't need them.
+        while let Some((node, distance)) = search.peek_next() {
+            if node == target {
+                return Ok(Some(Route {
+                    distance,
+                    path: search.path_to(target),
+                }));
+            }
+            search.settle_next(adjacencies);
+        }
+        match overflow_on_path_to(adjacencies, &search.overflows, target, |_, _| true) {
+            Some(overflow) => Err(overflow),
+            None => Ok(None),
+        }
+    }
+
+    // Like shortest_path, but searches forwards from source and backwards from target at the
+    // same time, which settles far fewer nodes on large graphs. reversed must be
+    // self.reversed(); it's passed in so it can be built once and used for many queries.
+    pub fn bidirectional_shortest_path(
+        &self,
+        reversed: &AdjacencyList<W>,
+        source: usize,
+        target: usize,
+    ) -> Result<Option<Route<W>>, Overflow> {
+        let (AdjacencyList(adjacencies), AdjacencyList(reversed_adjacencies)) = (self, reversed);
+        let mut forward: Search<W> = Search::new(self.num_nodes(), source);
+        let mut backward = Search::new(self.num_nodes(), target);
+        // The shortest path found so far, as its length and the node where the two searches
+        // met.
+        let mut best: Option<(W, usize)> = (source == target).then_some((W::ZERO, source));
+        // Whether any path was left out for being too long for W.
+        let mut overflowed = false;
+
+        // Once either side runs out of nodes there's nothing left to find.
+        while let (Some((_, forward_dist)), Some((_, backward_dist))) =
+            (forward.peek_next(), backward.peek_next())
+        {
+            // Any path we haven't seen yet has to leave both settled regions, so it's at least
+            // this long. If even that overflows, no such path can beat best.
+            if best.is_some_and(|(dist, _)| {
+                forward_dist
+                    .checked_add(backward_dist)
+                    .is_none_or(|bound| bound >= dist)
+            }) {
+                break;
+            }
+
+            // Expand whichever side has the closer frontier, then check whether any edge it
+            // relaxed joins up with the other side.
+            let (search, other, side_adjacencies) = if forward_dist <= backward_dist {
+                (&mut forward, &backward, adjacencies)
+            } else {
+                (&mut backward, &forward, reversed_adjacencies)
+            };
+            let node = search
+                .settle_next(side_adjacencies)
+                .expect("peek_next found a node to settle");
+            let node_dist = search.distances[node].expect("Settled nodes have a distance");
+            // settle_next already skipped the edges where node_dist + edge.dist overflows.
+            let candidates = side_adjacencies[node]
+                .iter()
+                .map(|edge| (edge.destination, node_dist.checked_add(edge.dist)))
+                .chain([(node, Some(node_dist))]);
+            for (meeting_node, dist) in candidates {
+                let (dist, other_dist) = match (dist, other.distances[meeting_node]) {
+                    (Some(dist), Some(other_dist)) => (dist, other_dist),
+                    _ => continue,
+                };
+                let Some(dist) = dist.checked_add(other_dist) else {
+                    overflowed = true;
+                    continue;
+                };
+                if best.is_none_or(|(best_dist, _)| dist < best_dist) {
+                    best = Some((dist, meeting_node));
+                }
+            }
+        }
+
+        overflowed |= !forward.overflows.is_empty() || !backward.overflows.is_empty();
+        let (distance, meeting_node) = match best {
+            Some(best) => best,
+            // Working out which overflow kept the two sides apart is simplest from one side.
+            None if overflowed => return self.shortest_path(source, target),
+            None => return Ok(None),
+        };
+        let mut path = forward.path_to(meeting_node);
+        let mut rest = backward.path_to(meeting_node);
+        rest.reverse();
+        path.extend(&rest[1..]);
+        Ok(Some(Route { distance, path }))
+    }
+
+    // Like shortest_path, but uses A* search, always settling the node with the smallest
+    // distance from source plus estimated distance to target. With a good heuristic this
+    // heads straight for target instead of searching in every direction.
+    pub fn a_star(
+        &self,
+        source: usize,
+        target: usize,
+        heuristic: &impl Heuristic<W>,
+    ) -> Result<Option<Route<W>>, Overflow> {
+        let AdjacencyList(adjacencies) = self;
+        let mut distances: Vec<Option<W>> = vec![None; self.num_nodes()];
+        let mut predecessors: Vec<Option<usize>> = vec![None; self.num_nodes()];
+        // Entries are keyed on distance plus estimate rather than distance alone. keys[i] is
+        // the key node i was last queued with, so older entries can be skipped.
+        let mut node_queue: BinaryHeap<Edge<W>> = BinaryHeap::new();
+        let mut keys: Vec<Option<W>> = vec![None; self.num_nodes()];
+        let mut overflows = vec![];
+
+        distances[source] = Some(W::ZERO);
+        keys[source] = Some(heuristic.estimate(source, target));
+        node_queue.push(Edge {
+            destination: source,
+            dist: heuristic.estimate(source, target),
+        });
+
+        while let Some(Edge { destination, dist }) = node_queue.pop() {
+            if Some(dist) != keys[destination] {
+                continue;
+            }
+            let path_dist = distances[destination].expect("Queued nodes have a distance");
+            if destination == target {
+                let mut path = vec![target];
+                while let Some(predecessor) = predecessors[*path.last().unwrap()] {
+                    path.push(predecessor);
+                }
+                path.reverse();
+                return Ok(Some(Route {
+                    distance: path_dist,
+                    path,
+                }));
+            }
+            // A node can be reached again by a shorter path if the heuristic isn't consistent,
+            // in which case it's simply queued up again. The heuristic is admissible, so if the
+            // key overflows then so does every path to target through the edge.
+            for edge in &adjacencies[destination] {
+                let overflow = Overflow {
+                    from: destination,
+                    to: edge.destination,
+                };
+                let Some(new_path_dist) = path_dist.checked_add(edge.dist) else {
+                    overflows.push(overflow);
+                    continue;
+                };
+                if distances[edge.destination].is_none_or(|dist| new_path_dist < dist) {
+                    let Some(key) =
+                        new_path_dist.checked_add(heuristic.estimate(edge.destination, target))
+                    else {
+                        overflows.push(overflow);
+                        continue;
+                    };
+                    distances[edge.destination] = Some(new_path_dist);
+                    predecessors[edge.destination] = Some(destination);
+                    keys[edge.destination] = Some(key);
+                    node_queue.push(Edge {
+                        destination: edge.destination,
+                        dist: key,
+                    });
+                }
+            }
+        }
+
+        match overflow_on_path_to(adjacencies, &overflows, target, |_, _| true) {
+            Some(overflow) => Err(overflow),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::XorShift;
+
+    // Checks that route really is a path in graph with the length it claims.
+    fn assert_route<W: Weight>(
+        graph: &AdjacencyList<W>,
+        source: usize,
+        target: usize,
+        route: &Route<W>,
+    ) {
+        let AdjacencyList(adjacencies) = graph;
+        assert_eq!(route.path.first(), Some(&source));
+        assert_eq!(route.path.last(), Some(&target));
+        let length = route.path.windows(2).fold(W::ZERO, |length, pair| {
+            let step = adjacencies[pair[0]]
+                .iter()
+                .filter(|edge| edge.destination == pair[1])
+                .map(|edge| edge.dist)
+                .min()
+                .expect("Every step of the path should be an edge");
+            length.checked_add(step).unwrap()
+        });
+        assert_eq!(length, route.distance);
+    }
+
+    // Checks that every query agrees with plain Dijkstra for every pair of nodes.
+    fn assert_agrees_with_dijkstra<W: Weight>(
+        graph: &AdjacencyList<W>,
+        heuristic: &impl Heuristic<W>,
+    ) {
+        let reversed = graph.reversed();
+        for source in 1..graph.num_nodes() {
+            let distances = graph.dijkstra(source).unwrap().distances;
+            for (target, &distance) in distances.iter().enumerate().skip(1) {
+                let routes = [
+                    graph.shortest_path(source, target).unwrap(),
+                    graph
+                        .bidirectional_shortest_path(&reversed, source, target)
+                        .unwrap(),
+                    graph.a_star(source, target, heuristic).unwrap(),
+                ];
+                for route in routes {
+                    assert_eq!(route.as_ref().map(|route| route.distance), distance);
+                    if let Some(route) = route {
+                        assert_route(graph, source, target, &route);
+                    }
+                }
+            }
+        }
+    }
+
+    // Random nodes in a 1000 by 1000 square, with edges at least as long as the straight line
+    // between their ends, so EuclideanDistance is admissible.
+    fn random_geometric_graph(
+        seed: u64,
+        num_nodes: usize,
+        num_edges: usize,
+    ) -> (AdjacencyList, EuclideanDistance) {
+        let mut rng = XorShift::new(seed);
+        let mut next = |n: usize| rng.below(n);
+        // Node 0 is the unused dummy node, like in parse_adjacencylist.
+        let coordinates: Vec<(f64, f64)> = (0..=num_nodes)
+            .map(|_| (next(1000) as f64, next(1000) as f64))
+            .collect();
+        let heuristic = EuclideanDistance { coordinates };
+        let mut adjacencies: Vec<Vec<Edge>> = (0..=num_nodes).map(|_| vec![]).collect();
+        for _ in 0..num_edges {
+            let (start, destination) = (next(num_nodes) + 1, next(num_nodes) + 1);
+            let estimate: usize = heuristic.estimate(start, destination);
+            let dist = estimate + 1 + next(100);
+            adjacencies[start].push(Edge { destination, dist });
+        }
+        (AdjacencyList(adjacencies), heuristic)
+    }
+
+    fn to_not_nan(graph: &AdjacencyList) -> AdjacencyList<NotNan> {
+        let AdjacencyList(adjacencies) = graph;
+        let adjacencies = adjacencies
+            .iter()
+            .map(|edges| {
+                edges
+                    .iter()
+                    .map(|edge| Edge {
+                        destination: edge.destination,
+                        // Not a whole number, so the Euclidean distance isn't rounded down.
+                        dist: NotNan::new(edge.dist as f64 - 0.5).unwrap(),
+                    })
+                    .collect()
+            })
+            .collect();
+        AdjacencyList(adjacencies)
+    }
+
+    #[test]
+    fn test_reversed() {
+        let graph: AdjacencyList = AdjacencyList::parse_adjacencylist("testfiles/test1.txt");
+        let AdjacencyList(reversed) = graph.reversed();
+        assert_eq!(
+            reversed,
+            vec![
+                vec![],
+                vec![
+                    Edge {
+                        destination: 2,
+                        dist: 10
+                    },
+                    Edge {
+                        destination: 3,
+                        dist: 2
+                    }
+                ],
+                vec![Edge {
+                    destination: 1,
+                    dist: 30
+                }],
+                vec![
+                    Edge {
+                        destination: 1,
+                        dist: 12
+                    },
+                    Edge {
+                        destination: 2,
+                        dist: 40
+                    }
+                ],
+            ]
+        );
+        assert_eq!(graph.reversed().reversed().num_nodes(), graph.num_nodes());
+    }
+
+    #[test]
+    fn test_point_to_point() {
+        let graph: AdjacencyList = AdjacencyList::parse_adjacencylist("testfiles/test1.txt");
+        let reversed = graph.reversed();
+        let expected = Ok(Some(Route {
+            distance: 22,
+            path: vec![2, 1, 3],
+        }));
+        assert_eq!(graph.shortest_path(2, 3), expected);
+        assert_eq!(graph.bidirectional_shortest_path(&reversed, 2, 3), expected);
+        assert_eq!(graph.a_star(2, 3, &|_, _| 0), expected);
+
+        let expected = Ok(Some(Route {
+            distance: 0,
+            path: vec![1],
+        }));
+        assert_eq!(graph.shortest_path(1, 1), expected);
+        assert_eq!(graph.bidirectional_shortest_path(&reversed, 1, 1), expected);
+        assert_eq!(graph.a_star(1, 1, &|_, _| 0), expected);
+
+        // Node 0 is the dummy node, which nothing can reach.
+        assert_eq!(graph.shortest_path(1, 0), Ok(None));
+        assert_eq!(graph.bidirectional_shortest_path(&reversed, 1, 0), Ok(None));
+        assert_eq!(graph.a_star(1, 0, &|_, _| 0), Ok(None));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // 1 -> 2 -> 3 is too long for a u8, but 1 -> 2 on its own isn't.
+        let graph: AdjacencyList<u8> = AdjacencyList(vec![
+            vec![],
+            vec![Edge {
+                destination: 2,
+                dist: 200,
+            }],
+            vec![Edge {
+                destination: 3,
+                dist: 100,
+            }],
+            vec![],
+        ]);
+        let reversed = graph.reversed();
+        let overflow = Overflow { from: 2, to: 3 };
+        assert_eq!(graph.shortest_path(1, 3), Err(overflow));
+        assert_eq!(graph.a_star(1, 3, &|_, _| 0), Err(overflow));
+        assert_eq!(
+            graph.bidirectional_shortest_path(&reversed, 1, 3),
+            Err(overflow)
+        );
+        assert_eq!(graph.shortest_path(1, 2).unwrap().unwrap().distance, 200);
+    }
+
+    #[test]
+    fn test_overflow_beside_shorter_path() {
+        // 1 -> 2 -> 3 overflows a u8, but 1 -> 3 is shorter anyway. Nothing reaches 4.
+        let graph: AdjacencyList<u8> = "1 2,200 3,5\n2 3,100\n4\n".parse().unwrap();
+        let reversed = graph.reversed();
+        let expected = Ok(Some(Route {
+            distance: 5,
+            path: vec![1, 3],
+        }));
+        assert_eq!(graph.shortest_path(1, 3), expected);
+        assert_eq!(graph.bidirectional_shortest_path(&reversed, 1, 3), expected);
+        assert_eq!(graph.a_star(1, 3, &|_, _| 0), expected);
+
+        assert_eq!(graph.shortest_path(1, 4), Ok(None));
+        assert_eq!(graph.bidirectional_shortest_path(&reversed, 1, 4), Ok(None));
+        assert_eq!(graph.a_star(1, 4, &|_, _| 0), Ok(None));
+
+        // A heuristic that overflows only rules out paths that would overflow anyway.
+        assert_eq!(
+            graph.a_star(1, 3, &|node, _| if node == 2 { 100 } else { 0 }),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_dijkstra_data() {
+        let graph: AdjacencyList = AdjacencyList::parse_adjacencylist("dijkstraData.txt");
+        let reversed = graph.reversed();
+        let distances = graph.dijkstra(1).unwrap().distances;
+        for target in [7, 37, 59, 82, 99, 115, 133, 165, 188, 197] {
+            for route in [
+                graph.shortest_path(1, target),
+                graph.bidirectional_shortest_path(&reversed, 1, target),
+                graph.a_star(1, target, &|_, _| 0),
+            ] {
+                let route = route.unwrap().unwrap();
+                assert_eq!(Some(route.distance), distances[target]);
+                assert_route(&graph, 1, target, &route);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_graphs() {
+        for seed in 1..=10 {
+            let (graph, heuristic) = random_geometric_graph(seed, 30, 90);
+            assert_agrees_with_dijkstra(&graph, &heuristic);
+            assert_agrees_with_dijkstra(&graph, &|_, _| 0);
+            assert_agrees_with_dijkstra(&to_not_nan(&graph), &heuristic);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+// The edge weights the shortest path code can work with. Weights must be totally ordered and
+// add up without wrapping around: checked_add returns None when the sum can't be represented,
+// which the algorithms report as an Overflow error. Dijkstra's algorithm also assumes weights
+// are never less than ZERO.
+pub trait Weight: Copy + Ord + fmt::Debug {
+    const ZERO: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_weight_for_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Weight for $t {
+                const ZERO: Self = 0;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_weight_for_unsigned!(u8, u16, u32, u64, u128, usize);
+
+// An f64 that is never NaN, so it has a total order and can be used as a Weight. -0.0 is
+// stored as 0.0 so that equal weights compare equal.
+#[derive(Debug, Clone, Copy)]
+pub struct NotNan(f64);
+
+impl NotNan {
+    // Returns value as a NotNan, or None if it is NaN.
+    pub fn new(value: f64) -> Option<NotNan> {
+        if value.is_nan() {
+            None
+        } else {
+            // Adding 0.0 turns -0.0 into 0.0 and leaves everything else alone.
+            Some(NotNan(value + 0.0))
+        }
+    }
+
+    pub fn get(self) -> f64 {
+        self.0
+    }
+}
+
+impl PartialEq for NotNan {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for NotNan {}
+
+impl Ord for NotNan {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl PartialOrd for NotNan {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Weight for NotNan {
+    const ZERO: Self = NotNan(0.0);
+
+    // Adding two finite weights overflows if the result is infinite. Infinite weights are
+    // allowed, but adding infinities of opposite signs has no answer.
+    fn checked_add(self, other: Self) -> Option<Self> {
+        let sum = self.0 + other.0;
+        if sum.is_infinite() && self.0.is_finite() && other.0.is_finite() {
+            return None;
+        }
+        NotNan::new(sum)
+    }
+}
+
+// Everything that can go wrong parsing a NotNan.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseNotNanError {
+    // The string isn't a number at all.
+    Invalid,
+    // The string is a number, but it's NaN.
+    NaN,
+}
+
+impl fmt::Display for ParseNotNanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseNotNanError::Invalid => write!(f, "invalid float literal"),
+            ParseNotNanError::NaN => write!(f, "weights can't be NaN"),
+        }
+    }
+}
+
+impl std::error::Error for ParseNotNanError {}
+
+impl FromStr for NotNan {
+    type Err = ParseNotNanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value: f64 = s.parse().map_err(|_| ParseNotNanError::Invalid)?;
+        NotNan::new(value).ok_or(ParseNotNanError::NaN)
+    }
+}
+
+impl fmt::Display for NotNan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// Returned when the length of a path is too large for the weight type. The path reaches to
+// through the edge from -> to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Overflow {
+    pub from: usize,
+    pub to: usize,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "path length overflowed following the edge {} -> {}",
+            self.from, self.to
+        )
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_add() {
+        assert_eq!(Weight::checked_add(2u32, 3), Some(5));
+        assert_eq!(Weight::checked_add(u32::MAX, 1), None);
+        assert_eq!(Weight::checked_add(u64::MAX, 0), Some(u64::MAX));
+        assert_eq!(<usize as Weight>::ZERO, 0);
+    }
+
+    #[test]
+    fn test_not_nan() {
+        let weight = |value| NotNan::new(value).unwrap();
+        assert_eq!(NotNan::new(f64::NAN), None);
+        assert_eq!(weight(-0.0), weight(0.0));
+        assert!(weight(0.5) < weight(1.5));
+        assert_eq!(weight(0.5).checked_add(weight(1.5)), Some(weight(2.0)));
+        assert_eq!(weight(f64::MAX).checked_add(weight(f64::MAX)), None);
+        assert_eq!(
+            weight(f64::INFINITY).checked_add(weight(1.0)),
+            Some(weight(f64::INFINITY))
+        );
+        assert_eq!(
+            weight(f64::INFINITY).checked_add(weight(f64::NEG_INFINITY)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_not_nan() {
+        assert_eq!("0.25".parse(), Ok(NotNan::new(0.25).unwrap()));
+        assert_eq!("NaN".parse::<NotNan>(), Err(ParseNotNanError::NaN));
+        assert_eq!("x".parse::<NotNan>(), Err(ParseNotNanError::Invalid));
+    }
+}
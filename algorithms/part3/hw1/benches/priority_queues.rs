@@ -0,0 +1,44 @@
+// Compares prims_mst, which pushes every edge leaving the tree onto a BinaryHeap, against
+// prims_mst_indexed, which keeps one entry per vertex in an IndexedHeap and lowers its key
+// instead. Runs both on edges.txt.
+//
+// Run with `cargo bench`.
+use hw1::spanning_tree::AdjacencyList;
+use std::time::Instant;
+
+const ROUNDS: usize = 1000;
+
+// Runs f ROUNDS times and prints how long that took.
+fn time(label: &str, mut f: impl FnMut()) {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    println!("{:<40} {:>10.2?}", label, start.elapsed());
+}
+
+fn main() {
+    let graph = AdjacencyList::parse_file("edges.txt");
+    let num_vertices = graph.prims_mst().map_or(0, |edges| edges.len() + 1);
+    println!(
+        "{} vertices, {} edges, {} rounds",
+        num_vertices,
+        graph.num_edges(),
+        ROUNDS
+    );
+    // The lazy queue gets an entry for every edge that leaves the tree, while the indexed heap
+    // only ever holds vertices.
+    let (_, lazy_len) = graph.prims_mst_with_max_queue_len().unwrap();
+    let (_, indexed_len) = graph.prims_mst_indexed_with_max_queue_len().unwrap();
+    println!(
+        "largest queue: BinaryHeap {}, IndexedHeap {}",
+        lazy_len, indexed_len
+    );
+
+    time("prims_mst (BinaryHeap)", || {
+        graph.prims_mst();
+    });
+    time("prims_mst_indexed (IndexedHeap)", || {
+        graph.prims_mst_indexed();
+    });
+}
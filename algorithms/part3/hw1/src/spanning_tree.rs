@@ -1,5 +1,7 @@
+use indexed_heap::IndexedHeap;
 use std::{
     collections::{BinaryHeap, HashMap, HashSet},
+    fmt::Write,
     fs,
     str::FromStr,
 };
@@ -152,6 +154,13 @@ impl AdjacencyList {
     // of Edges in the spanning tree, or None if graph is empty. If graph is disconnected, returns
     // returns Some vector of Edges that span an arbitrary connected subset of the tree.
     pub fn prims_mst(&self) -> Option<Vec<Edge>> {
+        self.prims_mst_with_max_queue_len()
+            .map(|(spanning_edges, _)| spanning_edges)
+    }
+
+    // Same as prims_mst, but also returns the most edges the queue held at once, so its size
+    // can be compared with prims_mst_indexed's.
+    pub fn prims_mst_with_max_queue_len(&self) -> Option<(Vec<Edge>, usize)> {
         // Get an arbitrary key as starting vertex and put rest into a HashSet. Return None if
         // graph is empty.
         let (start_vertex, mut remaining_vertices) = self.init_vertices()?;
@@ -164,6 +173,8 @@ impl AdjacencyList {
         // queue, so we will just check to see if they are in remaining_vertices.
         let mut edge_queue: BinaryHeap<Edge> = BinaryHeap::new();
         self.add_valid_edges_to_queue(&start_vertex, &mut edge_queue, &mut remaining_vertices);
+        // Edges are only pushed by process_edge, so the queue is largest right after a call.
+        let mut max_queue_len = edge_queue.len();
 
         while !remaining_vertices.is_empty() {
             if let Some(edge) = edge_queue.pop() {
@@ -172,13 +183,71 @@ impl AdjacencyList {
                     &mut edge_queue,
                     &mut spanning_edges,
                     &mut remaining_vertices,
-                )
+                );
+                max_queue_len = max_queue_len.max(edge_queue.len());
             } else {
                 // This is only reached if the graph is not connected.
-                return Some(spanning_edges);
+                return Some((spanning_edges, max_queue_len));
+            }
+        }
+        Some((spanning_edges, max_queue_len))
+    }
+
+    // Same as prims_mst, but keeps one entry per vertex outside the tree in an IndexedHeap,
+    // keyed by the cheapest edge joining it to the tree, and lowers that key when a cheaper
+    // edge turns up. The queue never holds more than one entry per vertex.
+    pub fn prims_mst_indexed(&self) -> Option<Vec<Edge>> {
+        self.prims_mst_indexed_with_max_queue_len()
+            .map(|(spanning_edges, _)| spanning_edges)
+    }
+
+    // Same as prims_mst_indexed, but also returns the most vertices the queue held at once.
+    pub fn prims_mst_indexed_with_max_queue_len(&self) -> Option<(Vec<Edge>, usize)> {
+        // Vertices can be any u32, so number them 0..n for the heap.
+        let mut vertices: Vec<Vertex> = self.0.keys().copied().collect();
+        vertices.sort_unstable();
+        let start_vertex = *vertices.first()?;
+        let indices: HashMap<Vertex, usize> = vertices
+            .iter()
+            .enumerate()
+            .map(|(index, &vertex)| (vertex, index))
+            .collect();
+
+        let mut spanning_edges: Vec<Edge> = vec![];
+        let mut in_tree = vec![false; vertices.len()];
+        // cheapest_edges[index] is the vertex at the other end of the edge that gave vertex
+        // index its current key.
+        let mut cheapest_edges: Vec<Option<Vertex>> = vec![None; vertices.len()];
+        let mut vertex_queue: IndexedHeap<Cost> = IndexedHeap::new(vertices.len());
+        vertex_queue.push(indices[&start_vertex], 0);
+        let mut max_queue_len = vertex_queue.len();
+
+        // Once the queue is empty, every vertex reachable from the start is in the tree.
+        while let Some((index, cost)) = vertex_queue.pop() {
+            let vertex = vertices[index];
+            in_tree[index] = true;
+            if let Some(from) = cheapest_edges[index] {
+                spanning_edges.push(Edge {
+                    from,
+                    to: vertex,
+                    cost,
+                });
+            }
+            for al_edge in &self.0[&vertex] {
+                let to_index = indices[&al_edge.to];
+                if !in_tree[to_index] && vertex_queue.push_or_decrease(to_index, al_edge.cost) {
+                    cheapest_edges[to_index] = Some(vertex);
+                }
             }
+            max_queue_len = max_queue_len.max(vertex_queue.len());
         }
-        Some(spanning_edges)
+        Some((spanning_edges, max_queue_len))
+    }
+
+    // Returns the number of edges in the graph.
+    pub fn num_edges(&self) -> usize {
+        // Every edge is stored once for each of its ends.
+        self.0.values().map(Vec::len).sum::<usize>() / 2
     }
 
     // Given a vertex, adds all valid (i.e. `to` vertex is not already in spanning tree) outgoing
@@ -218,14 +287,64 @@ impl AdjacencyList {
         }
     }
 
-    // Picks an arbitrary starting vertex and returns that and a HashSet of the remaining vertices.
+    // Picks the smallest vertex as the starting vertex, so that ties between edges of equal
+    // cost are always broken the same way, and returns that and a HashSet of the remaining
+    // vertices.
     fn init_vertices(&self) -> Option<(Vertex, HashSet<Vertex>)> {
-        let mut key_iter = self.0.keys().copied();
-        if let Some(vertex) = key_iter.next() {
-            let remaining_vertices = key_iter.collect();
-            return Some((vertex, remaining_vertices));
+        let vertex = *self.0.keys().min()?;
+        let remaining_vertices = self.0.keys().copied().filter(|&v| v != vertex).collect();
+        Some((vertex, remaining_vertices))
+    }
+
+    // Returns the graph in Graphviz DOT format with every edge labelled by its cost and the
+    // edges found by prims_mst drawn in bold. Vertices and edges are written in sorted order,
+    // so the same graph always gives the same output.
+    pub fn to_dot(&self) -> String {
+        // Counts the spanning tree edges between each pair of vertices with each cost, so that
+        // only that many of any parallel edges are drawn in bold.
+        let mut tree_edges: HashMap<(Vertex, Vertex, Cost), usize> = HashMap::new();
+        for edge in self.prims_mst().unwrap_or_default() {
+            let key = (edge.from.min(edge.to), edge.from.max(edge.to), edge.cost);
+            *tree_edges.entry(key).or_default() += 1;
+        }
+
+        let mut vertices: Vec<Vertex> = self.0.keys().copied().collect();
+        vertices.sort_unstable();
+
+        let mut dot = String::from("graph {\n");
+        for vertex in &vertices {
+            writeln!(dot, "    {};", vertex).unwrap();
         }
-        None
+        for &from in &vertices {
+            // Every edge is stored once for each of its ends, so only write it out from the
+            // smaller one. A self loop is stored twice in the same list, so we skip every
+            // second copy of it.
+            let mut edges: Vec<(Vertex, Cost)> = self.0[&from]
+                .iter()
+                .filter(|al_edge| al_edge.to >= from)
+                .map(|al_edge| (al_edge.to, al_edge.cost))
+                .collect();
+            edges.sort_unstable();
+            let mut skip_self_loop = true;
+            for (to, cost) in edges {
+                if to == from {
+                    skip_self_loop = !skip_self_loop;
+                    if skip_self_loop {
+                        continue;
+                    }
+                }
+                let style = match tree_edges.get_mut(&(from, to, cost)) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        ", style=bold"
+                    }
+                    _ => "",
+                };
+                writeln!(dot, "    {} -- {} [label=\"{}\"{}];", from, to, cost, style).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
     }
 }
 
@@ -306,4 +425,69 @@ mod tests {
         spanning_tree.sort_by_key(|edge| edge.cost);
         assert_eq!(correct_tree, spanning_tree)
     }
+
+    #[test]
+    fn test_prims_indexed() {
+        let graph = setup_adj_list();
+        let mut spanning_tree = graph.prims_mst_indexed().expect("Didn't expect None");
+        spanning_tree.sort_by_key(|edge| edge.cost);
+        let mut correct_tree = graph.prims_mst().unwrap();
+        correct_tree.sort_by_key(|edge| edge.cost);
+        assert_eq!(correct_tree, spanning_tree);
+        assert_eq!(AdjacencyList::new().prims_mst_indexed(), None);
+
+        let graph = AdjacencyList::parse_file("edges.txt");
+        assert_eq!(graph.num_edges(), 2184);
+        let total_cost = |edges: Vec<Edge>| edges.iter().map(|edge| edge.cost).sum::<Cost>();
+        let spanning_tree = graph.prims_mst_indexed().unwrap();
+        assert_eq!(spanning_tree.len(), 499);
+        assert_eq!(total_cost(spanning_tree), total_cost(graph.prims_mst().unwrap()));
+
+        // The indexed heap holds at most one entry per vertex, the lazy queue one per edge.
+        let (_, lazy_len) = graph.prims_mst_with_max_queue_len().unwrap();
+        let (_, indexed_len) = graph.prims_mst_indexed_with_max_queue_len().unwrap();
+        assert!(indexed_len < 500 && indexed_len < lazy_len && lazy_len <= 2 * 2184);
+    }
+
+    #[test]
+    fn test_prims_indexed_disconnected() {
+        // Like prims_mst, only the part containing the smallest vertex is spanned.
+        let mut graph = AdjacencyList::new();
+        for (from, to, cost) in [(1, 2, 5), (2, 3, 1), (1, 3, 2), (4, 5, 1)] {
+            graph.push_edge(Edge { from, to, cost });
+        }
+        let expected = vec![
+            Edge {
+                from: 1,
+                to: 3,
+                cost: 2,
+            },
+            Edge {
+                from: 3,
+                to: 2,
+                cost: 1,
+            },
+        ];
+        assert_eq!(graph.prims_mst_indexed(), Some(expected));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let graph = AdjacencyList::parse_file("testfile2.txt");
+        assert_eq!(graph.to_dot(), fs::read_to_string("testfile2.dot").unwrap())
+    }
+
+    #[test]
+    fn test_to_dot_parallel_edges_and_self_loops() {
+        let mut graph = AdjacencyList::new();
+        for (from, to, cost) in [(2, 1, 5), (1, 2, 5), (1, 1, 0), (2, 3, 1), (3, 1, 9)] {
+            graph.push_edge(Edge { from, to, cost });
+        }
+        assert_eq!(
+            graph.to_dot(),
+            "graph {\n    1;\n    2;\n    3;\n    1 -- 1 [label=\"0\"];\n    \
+             1 -- 2 [label=\"5\", style=bold];\n    1 -- 2 [label=\"5\"];\n    \
+             1 -- 3 [label=\"9\"];\n    2 -- 3 [label=\"1\", style=bold];\n}\n"
+        )
+    }
 }
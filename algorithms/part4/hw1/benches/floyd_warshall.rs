@@ -0,0 +1,35 @@
+// Compares the Floyd-Warshall variants, and Johnson's algorithm, on g1.txt to g3.txt. The
+// original floyd_warshall copies the whole matrix for every vertex, so it takes a while.
+//
+// Run with `cargo bench`.
+use hw1::apsp::{Distance, Graph};
+use std::{fs, time::Instant};
+
+// Runs f and prints its result and how long it took.
+fn time(label: &str, f: impl FnOnce() -> Distance) {
+    let start = Instant::now();
+    let result = f();
+    println!("{:<30} {:>10.2?}   {:?}", label, start.elapsed(), result);
+}
+
+fn main() {
+    for filename in ["g1.txt", "g2.txt", "g3.txt"] {
+        let graph = Graph::from_str(&fs::read_to_string(filename).unwrap());
+        println!(
+            "{}: {} vertices, {} edges",
+            filename,
+            graph.len(),
+            graph.edges().len()
+        );
+        time("floyd_warshall", || graph.floyd_warshall());
+        time("floyd_warshall_in_place", || {
+            graph.floyd_warshall_in_place()
+        });
+        time("floyd_warshall_blocked", || graph.floyd_warshall_blocked());
+        time("floyd_warshall_parallel", || {
+            graph.floyd_warshall_parallel()
+        });
+        time("johnson", || graph.johnson());
+        println!();
+    }
+}
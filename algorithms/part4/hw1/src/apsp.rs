@@ -1,18 +1,18 @@
-use std::{cmp::Ordering, ops::Add};
+use std::{cmp::Ordering, fmt::Write, ops::Add};
 
-type Vertex = usize;
-type Dist = isize;
+pub type Vertex = usize;
+pub type Dist = isize;
 
 // Edge for adjacency list
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub struct Edge {
-    head: Vertex,
-    distance: Dist,
+    pub(crate) head: Vertex,
+    pub(crate) distance: Dist,
 }
 
 // Adjacency list
 #[derive(Debug, Eq, PartialEq, Clone)]
-pub struct Graph(Vec<Vec<Edge>>);
+pub struct Graph(pub(crate) Vec<Vec<Edge>>);
 
 // Integers don't have inifinity but it sure would be cooler if they did
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
@@ -82,6 +82,23 @@ impl Graph {
             .collect()
     }
 
+    // Returns the graph in Graphviz DOT format with every edge labelled by its distance.
+    // Vertices and edges are written in sorted order, so the same graph always gives the same
+    // output.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        for v in 1..=self.len() {
+            writeln!(dot, "    {};", v).unwrap();
+        }
+        let mut edges = self.edges();
+        edges.sort_unstable();
+        for (tail, head, distance) in edges {
+            writeln!(dot, "    {} -> {} [label=\"{}\"];", tail, head, distance).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
     // Computes the all pairs shortest paths for the given Graph. Returns Finite(distance) if
     // There are no negative cycles. Returns Infinite if a negative cycle is detected.
     pub fn floyd_warshall(&self) -> Distance {
@@ -139,8 +156,9 @@ impl PathDistances {
             weight_data[v][v] = Distance::Finite(0);
         }
 
+        // Of several parallel edges, only the shortest matters.
         for (tail, head, dist) in graph.edges() {
-            weight_data[tail][head] = Distance::Finite(dist);
+            weight_data[tail][head] = weight_data[tail][head].min(Distance::Finite(dist));
         }
         Self(weight_data)
     }
@@ -180,10 +198,11 @@ impl PathDistances {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use std::fs;
 
-    use super::{Distance, Edge, Graph, PathDistances, Vertex};
+    use super::{Dist, Distance, Edge, Graph, PathDistances, Vertex};
+    use xorshift::XorShift;
 
     impl Edge {
         fn from(head: Vertex, distance: isize) -> Self {
@@ -191,6 +210,19 @@ mod tests {
         }
     }
 
+    // Returns a random graph on n vertices with up to 3n edges, whose distances run from
+    // -offset to 29 - offset. There can be parallel edges and self loops.
+    pub(crate) fn random_graph(rng: &mut XorShift, n: usize, offset: Dist) -> Graph {
+        let mut adjacency_list: Vec<Vec<Edge>> = vec![vec![]; n + 1];
+        for _ in 0..rng.below(3 * n) {
+            let tail = 1 + rng.below(n);
+            let head = 1 + rng.below(n);
+            let distance = rng.below(30) as Dist - offset;
+            adjacency_list[tail].push(Edge { head, distance });
+        }
+        Graph(adjacency_list)
+    }
+
     fn graph() -> Graph {
         Graph(vec![
             vec![],
@@ -314,4 +346,24 @@ mod tests {
     fn test_floyd_warshall_cycle() {
         assert_eq!(negcycle_graph().floyd_warshall(), Distance::Infinite)
     }
+
+    #[test]
+    fn test_to_dot() {
+        assert_eq!(graph().to_dot(), fs::read_to_string("testgraph.dot").unwrap())
+    }
+
+    #[test]
+    fn test_to_dot_sorts_edges() {
+        let graph = Graph(vec![
+            vec![],
+            vec![Edge::from(3, 4), Edge::from(2, 7), Edge::from(2, -3)],
+            vec![],
+            vec![Edge::from(1, 0)],
+        ]);
+        assert_eq!(
+            graph.to_dot(),
+            "digraph {\n    1;\n    2;\n    3;\n    1 -> 2 [label=\"-3\"];\n    \
+             1 -> 2 [label=\"7\"];\n    1 -> 3 [label=\"4\"];\n    3 -> 1 [label=\"0\"];\n}\n"
+        )
+    }
 }
@@ -0,0 +1,359 @@
+use crate::apsp::{Dist, Distance, Graph, Vertex};
+use std::{collections::VecDeque, fmt};
+
+// The shortest paths from source to every vertex of a graph with no negative cycles.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ShortestPaths {
+    pub source: Vertex,
+    // distances[v] is the distance from source to v, or Infinite if v isn't reachable.
+    // distances[0] is always Infinite, since there is no vertex 0.
+    pub distances: Vec<Distance>,
+    // predecessors[v] is the vertex before v on a shortest path from source, or None if v is
+    // source or isn't reachable from it.
+    pub predecessors: Vec<Option<Vertex>>,
+}
+
+// Returned instead of distances when a negative cycle can be reached from the source, since
+// then paths through the cycle get shorter forever. Holds the vertices of one such cycle in
+// the order they're visited, starting with the smallest, so each vertex has an edge to the
+// next and the last has an edge back to the first.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct NegativeCycle(pub Vec<Vertex>);
+
+impl fmt::Display for NegativeCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "negative cycle through ")?;
+        for v in &self.0 {
+            write!(f, "{} -> ", v)?;
+        }
+        write!(f, "{}", self.0[0])
+    }
+}
+
+impl std::error::Error for NegativeCycle {}
+
+impl ShortestPaths {
+    // Returns a shortest path from source to target, starting with source and ending with
+    // target, or None if target isn't reachable.
+    pub fn path_to(&self, target: Vertex) -> Option<Vec<Vertex>> {
+        if *self.distances.get(target)? == Distance::Infinite {
+            return None;
+        }
+        let mut path = vec![target];
+        while let Some(predecessor) = self.predecessors[*path.last().unwrap()] {
+            path.push(predecessor);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+impl Graph {
+    // Computes the shortest paths from source with the Bellman-Ford algorithm, which relaxes
+    // every edge n - 1 times. Unlike Dijkstra's algorithm this works with negative distances,
+    // and a further round that still finds a shorter path means there's a negative cycle.
+    // Takes O(nm) time.
+    pub fn bellman_ford(&self, source: Vertex) -> Result<ShortestPaths, NegativeCycle> {
+        let mut distances = vec![Distance::Infinite; self.len() + 1];
+        distances[source] = Distance::Finite(0);
+        let predecessors = self.relax_every_edge(&mut distances)?;
+        Ok(ShortestPaths {
+            source,
+            distances,
+            predecessors,
+        })
+    }
+
+    // Runs the rounds of the Bellman-Ford algorithm starting from distances, until nothing
+    // changes or there's a negative cycle. Starting every vertex at 0 is the same as starting
+    // from a new vertex with an edge of distance 0 to each of them. Returns the predecessors
+    // on the shortest paths found, which are None for vertices nothing shorter was found for.
+    pub(crate) fn relax_every_edge(
+        &self,
+        distances: &mut [Distance],
+    ) -> Result<Vec<Option<Vertex>>, NegativeCycle> {
+        let edges = self.edges();
+        let mut predecessors = vec![None; self.len() + 1];
+
+        for _ in 0..self.len() {
+            let mut changed = false;
+            for &(tail, head, distance) in &edges {
+                if relax(distances, &mut predecessors, tail, head, distance) {
+                    changed = true;
+                }
+            }
+            if !changed {
+                return Ok(predecessors);
+            }
+        }
+
+        // Something still changed in round n, so the predecessors have a cycle in them.
+        Err(find_predecessor_cycle(&predecessors)
+            .expect("A change in round n means there's a negative cycle"))
+    }
+
+    // Like bellman_ford, but uses the Shortest Path Faster Algorithm, which only relaxes the
+    // edges leaving vertices whose distance changed, keeping those vertices in a queue. This
+    // has the same worst case but is usually much faster.
+    pub fn spfa(&self, source: Vertex) -> Result<ShortestPaths, NegativeCycle> {
+        let n = self.len();
+        let mut distances = vec![Distance::Infinite; n + 1];
+        let mut predecessors = vec![None; n + 1];
+        let mut queue: VecDeque<Vertex> = VecDeque::from([source]);
+        let mut queued = vec![false; n + 1];
+        distances[source] = Distance::Finite(0);
+        queued[source] = true;
+        // With a negative cycle the queue never empties, but the cycle eventually shows up in
+        // the predecessors. Looking for it takes O(n), so only look every n relaxations.
+        let mut relaxations = 0;
+
+        while let Some(tail) = queue.pop_front() {
+            queued[tail] = false;
+            for edge in &self.0[tail] {
+                if !relax(
+                    &mut distances,
+                    &mut predecessors,
+                    tail,
+                    edge.head,
+                    edge.distance,
+                ) {
+                    continue;
+                }
+                if !queued[edge.head] {
+                    queued[edge.head] = true;
+                    queue.push_back(edge.head);
+                }
+                relaxations += 1;
+                if relaxations % n == 0 {
+                    if let Some(cycle) = find_predecessor_cycle(&predecessors) {
+                        return Err(cycle);
+                    }
+                }
+            }
+        }
+
+        Ok(ShortestPaths {
+            source,
+            distances,
+            predecessors,
+        })
+    }
+}
+
+// Lowers the distance to head if going through tail is shorter. Returns true if it did.
+fn relax(
+    distances: &mut [Distance],
+    predecessors: &mut [Option<Vertex>],
+    tail: Vertex,
+    head: Vertex,
+    distance: Dist,
+) -> bool {
+    let new_distance = distances[tail] + Distance::Finite(distance);
+    if new_distance < distances[head] {
+        distances[head] = new_distance;
+        predecessors[head] = Some(tail);
+        true
+    } else {
+        false
+    }
+}
+
+// Looks for a cycle among the edges predecessor -> vertex. Every such cycle is negative,
+// since each edge on it was last relaxed after the distance to its tail was set, and going
+// around the cycle made the distances smaller.
+fn find_predecessor_cycle(predecessors: &[Option<Vertex>]) -> Option<NegativeCycle> {
+    // walked_from[v] is the vertex whose walk first reached v.
+    let mut walked_from: Vec<Option<Vertex>> = vec![None; predecessors.len()];
+    for start in 0..predecessors.len() {
+        let mut v = start;
+        while walked_from[v].is_none() {
+            walked_from[v] = Some(start);
+            v = match predecessors[v] {
+                Some(predecessor) => predecessor,
+                None => break,
+            };
+        }
+        if walked_from[v] != Some(start) || predecessors[v].is_none() {
+            continue;
+        }
+
+        // This walk came back to v, so v is on a cycle. Going around it through the
+        // predecessors visits the vertices backwards.
+        let mut cycle = vec![v];
+        let mut u = predecessors[v].unwrap();
+        while u != v {
+            cycle.push(u);
+            u = predecessors[u].unwrap();
+        }
+        cycle.reverse();
+        let smallest = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap();
+        cycle.rotate_left(smallest);
+        return Some(NegativeCycle(cycle));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apsp::tests::random_graph;
+    use crate::apsp::Edge;
+    use std::fs;
+    use xorshift::XorShift;
+
+    fn read_graph(filename: &str) -> Graph {
+        Graph::from_str(&fs::read_to_string(filename).unwrap())
+    }
+
+    // Runs both algorithms, checks they find the same distances, and returns what
+    // bellman_ford found. The paths and cycles they find can differ.
+    fn both(graph: &Graph, source: Vertex) -> Result<ShortestPaths, NegativeCycle> {
+        let result = graph.bellman_ford(source);
+        match (&result, graph.spfa(source)) {
+            (Ok(paths), Ok(spfa_paths)) => assert_eq!(paths.distances, spfa_paths.distances),
+            (Err(_), Err(_)) => {}
+            (result, spfa_result) => panic!("{:?} but spfa gave {:?}", result, spfa_result),
+        }
+        result
+    }
+
+    // Checks that cycle is a cycle in graph with a negative total distance.
+    fn assert_negative_cycle(graph: &Graph, NegativeCycle(cycle): &NegativeCycle) {
+        let next = cycle.iter().skip(1).chain(cycle.first());
+        let total: Dist = cycle
+            .iter()
+            .zip(next)
+            .map(|(&tail, &head)| {
+                graph.0[tail]
+                    .iter()
+                    .filter(|edge| edge.head == head)
+                    .map(|edge| edge.distance)
+                    .min()
+                    .expect("Each vertex should have an edge to the next")
+            })
+            .sum();
+        assert!(total < 0);
+        assert_eq!(cycle.iter().min(), cycle.first());
+    }
+
+    #[test]
+    fn test_testgraph() {
+        let graph = read_graph("testgraph.txt");
+        let paths = both(&graph, 1).unwrap();
+        assert_eq!(
+            paths.distances,
+            vec![
+                Distance::Infinite,
+                Distance::Finite(0),
+                Distance::Finite(1),
+                Distance::Finite(0)
+            ]
+        );
+        assert_eq!(paths.predecessors, vec![None, None, Some(1), Some(2)]);
+        assert_eq!(paths.path_to(3), Some(vec![1, 2, 3]));
+        assert_eq!(paths.path_to(0), None);
+
+        let paths = both(&graph, 3).unwrap();
+        assert_eq!(paths.path_to(2), Some(vec![3, 1, 2]));
+        assert_eq!(paths.distances[2], Distance::Finite(3));
+    }
+
+    #[test]
+    fn test_negative_cycle() {
+        let graph = read_graph("testgraph_negcycle.txt");
+        for source in 1..=graph.len() {
+            assert_eq!(graph.bellman_ford(source), Err(NegativeCycle(vec![2, 4])));
+            assert_eq!(graph.spfa(source), Err(NegativeCycle(vec![2, 4])));
+        }
+        assert_eq!(
+            NegativeCycle(vec![2, 4]).to_string(),
+            "negative cycle through 2 -> 4 -> 2"
+        );
+    }
+
+    #[test]
+    fn test_unreachable_negative_cycle() {
+        // 2 and 3 make a negative cycle, but nothing leads there from 1 or 4.
+        let graph = Graph(vec![
+            vec![],
+            vec![Edge {
+                head: 4,
+                distance: -5,
+            }],
+            vec![
+                Edge {
+                    head: 3,
+                    distance: 1,
+                },
+                Edge {
+                    head: 1,
+                    distance: 0,
+                },
+            ],
+            vec![Edge {
+                head: 2,
+                distance: -2,
+            }],
+            vec![],
+        ]);
+        let paths = both(&graph, 1).unwrap();
+        assert_eq!(
+            paths.distances,
+            vec![
+                Distance::Infinite,
+                Distance::Finite(0),
+                Distance::Infinite,
+                Distance::Infinite,
+                Distance::Finite(-5)
+            ]
+        );
+        assert_eq!(graph.spfa(3), Err(NegativeCycle(vec![2, 3])));
+        assert_eq!(graph.bellman_ford(3), Err(NegativeCycle(vec![2, 3])));
+    }
+
+    #[test]
+    fn test_matches_floyd_warshall_on_random_graphs() {
+        let mut rng = XorShift::new(0x2545_f491_4f6c_dd1d);
+        for _ in 0..200 {
+            let n = 1 + rng.below(10);
+            let graph = random_graph(&mut rng, n, 5);
+
+            // floyd_warshall gives the smallest distance between any two vertices, or Infinite
+            // if there's a negative cycle anywhere.
+            let mut min_dist = Distance::Infinite;
+            let mut has_negative_cycle = false;
+            for source in 1..=n {
+                match both(&graph, source) {
+                    Ok(paths) => {
+                        min_dist = min_dist.min(*paths.distances.iter().min().unwrap());
+                        for v in 1..=n {
+                            if let Some(path) = paths.path_to(v) {
+                                let length: Dist = path
+                                    .windows(2)
+                                    .map(|pair| {
+                                        graph.0[pair[0]]
+                                            .iter()
+                                            .filter(|edge| edge.head == pair[1])
+                                            .map(|edge| edge.distance)
+                                            .min()
+                                            .unwrap()
+                                    })
+                                    .sum();
+                                assert_eq!(Distance::Finite(length), paths.distances[v]);
+                            }
+                        }
+                    }
+                    Err(cycle) => {
+                        assert_negative_cycle(&graph, &cycle);
+                        assert_negative_cycle(&graph, &graph.spfa(source).unwrap_err());
+                        has_negative_cycle = true;
+                    }
+                }
+            }
+            if has_negative_cycle {
+                min_dist = Distance::Infinite;
+            }
+            assert_eq!(graph.floyd_warshall(), min_dist);
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use crate::apsp::{Distance, Graph};
+use std::sync::{Barrier, RwLock};
+use std::thread;
+
+// The number of rows and columns in each tile of floyd_warshall_blocked. Three 64 by 64 tiles
+// of Distances take up 192KiB, so the tiles being worked on stay in cache.
+pub const BLOCK_SIZE: usize = 64;
+
+// The distances between every pair of vertices in a single row-major buffer, so the distance
+// from u to v is at (u - 1) * n + (v - 1). Unlike PathDistances there's no row or column for
+// vertex 0.
+#[derive(Debug, Eq, PartialEq, Clone)]
+struct FlatDistances {
+    n: usize,
+    data: Vec<Distance>,
+}
+
+impl Graph {
+    // Same as floyd_warshall, but updates a single flat buffer in place instead of allocating
+    // a new matrix for every vertex.
+    pub fn floyd_warshall_in_place(&self) -> Distance {
+        let mut distances = FlatDistances::init(self);
+        distances.in_place();
+        distances.min_dist()
+    }
+
+    // Same as floyd_warshall, but works through the matrix in BLOCK_SIZE by BLOCK_SIZE tiles,
+    // which makes far better use of the cache on large graphs.
+    pub fn floyd_warshall_blocked(&self) -> Distance {
+        let mut distances = FlatDistances::init(self);
+        distances.blocked(BLOCK_SIZE);
+        distances.min_dist()
+    }
+
+    // Same as floyd_warshall_in_place, but splits the rows between one thread for each CPU.
+    pub fn floyd_warshall_parallel(&self) -> Distance {
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        let mut distances = FlatDistances::init(self);
+        distances.parallel(threads);
+        distances.min_dist()
+    }
+}
+
+impl FlatDistances {
+    // Given graph, initializes the distances between vertices like PathDistances::init.
+    fn init(graph: &Graph) -> Self {
+        let n = graph.len();
+        let mut data = vec![Distance::Infinite; n * n];
+        for v in 0..n {
+            data[v * n + v] = Distance::Finite(0);
+        }
+        for (tail, head, dist) in graph.edges() {
+            let entry = &mut data[(tail - 1) * n + (head - 1)];
+            *entry = (*entry).min(Distance::Finite(dist));
+        }
+        Self { n, data }
+    }
+
+    // Returns true if the distance from k to itself is negative, i.e. k is on a negative
+    // cycle.
+    fn on_negative_cycle(&self, k: usize) -> bool {
+        self.data[k * self.n + k] < Distance::Finite(0)
+    }
+
+    // Runs Floyd-Warshall in place. Row k doesn't change while k is the new interior vertex,
+    // unless k is on a negative cycle, so the rows can all be updated from a copy of it.
+    // A negative cycle whose largest vertex is k makes the distance from k to itself negative
+    // by the time k is reached. We stop there, since carrying on would keep going around the
+    // cycle and the distances could grow too large for a Dist. min_dist still sees the cycle.
+    fn in_place(&mut self) {
+        let n = self.n;
+        let mut k_row = vec![Distance::Infinite; n];
+        for k in 0..n {
+            if self.on_negative_cycle(k) {
+                return;
+            }
+            k_row.copy_from_slice(&self.data[k * n..(k + 1) * n]);
+            for row in self.data.chunks_mut(n) {
+                relax_row(row, k, &k_row);
+            }
+        }
+    }
+
+    // Runs Floyd-Warshall a tile at a time. For each diagonal tile, that tile is updated
+    // first, then the rest of its row and column of tiles, which only depend on it, and then
+    // everything else, which only depends on those. Like in_place, this stops at the first
+    // vertex found on a negative cycle, which happens while updating a diagonal tile.
+    fn blocked(&mut self, block_size: usize) {
+        let blocks = self.n.div_ceil(block_size);
+        for kb in 0..blocks {
+            if !self.update_block(kb, kb, kb, block_size) {
+                return;
+            }
+            for b in (0..blocks).filter(|&b| b != kb) {
+                self.update_block(kb, b, kb, block_size);
+                self.update_block(b, kb, kb, block_size);
+            }
+            for ib in (0..blocks).filter(|&ib| ib != kb) {
+                for jb in (0..blocks).filter(|&jb| jb != kb) {
+                    self.update_block(ib, jb, kb, block_size);
+                }
+            }
+        }
+    }
+
+    // Updates the distances in tile (ib, jb) to include paths through the vertices of tile
+    // kb. Returns false, leaving the rest of the tile alone, if it finds one of those
+    // vertices on a negative cycle.
+    fn update_block(&mut self, ib: usize, jb: usize, kb: usize, block_size: usize) -> bool {
+        let n = self.n;
+        let range = |b: usize| b * block_size..((b + 1) * block_size).min(n);
+        for k in range(kb) {
+            if self.on_negative_cycle(k) {
+                return false;
+            }
+            for i in range(ib) {
+                let via = self.data[i * n + k];
+                if via == Distance::Infinite {
+                    continue;
+                }
+                for j in range(jb) {
+                    let new_dist = via + self.data[k * n + j];
+                    if new_dist < self.data[i * n + j] {
+                        self.data[i * n + j] = new_dist;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    // Same as in_place, but the rows are split into threads contiguous chunks, each updated by
+    // its own thread for every k. The threads all wait at a barrier before starting on each k,
+    // by which time the thread holding row k has copied it into a shared buffer. There are two
+    // buffers, used for even and odd k, so row k + 1 can be copied while other threads are
+    // still reading row k.
+    fn parallel(&mut self, threads: usize) {
+        let n = self.n;
+        if n == 0 {
+            return;
+        }
+        let rows_per_thread = n.div_ceil(threads.max(1));
+        let chunks: Vec<&mut [Distance]> = self.data.chunks_mut(rows_per_thread * n).collect();
+        let barrier = Barrier::new(chunks.len());
+        let k_rows = [
+            RwLock::new(vec![Distance::Infinite; n]),
+            RwLock::new(vec![Distance::Infinite; n]),
+        ];
+        thread::scope(|scope| {
+            for (index, chunk) in chunks.into_iter().enumerate() {
+                let (barrier, k_rows) = (&barrier, &k_rows);
+                let first_row = index * rows_per_thread;
+                let rows = first_row..first_row + chunk.len() / n;
+                scope.spawn(move || {
+                    for k in 0..n {
+                        let k_row = &k_rows[k % 2];
+                        if rows.contains(&k) {
+                            let offset = (k - first_row) * n;
+                            k_row
+                                .write()
+                                .unwrap()
+                                .copy_from_slice(&chunk[offset..offset + n]);
+                        }
+                        barrier.wait();
+                        let k_row = k_row.read().unwrap();
+                        // Every thread sees the same row, so they all stop here together.
+                        if k_row[k] < Distance::Finite(0) {
+                            return;
+                        }
+                        for row in chunk.chunks_mut(n) {
+                            relax_row(row, k, &k_row);
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    // Same as PathDistances::min_dist.
+    fn min_dist(&self) -> Distance {
+        let n = self.n;
+        if (0..n).any(|v| self.data[v * n + v] < Distance::Finite(0)) {
+            Distance::Infinite
+        } else {
+            self.data
+                .iter()
+                .copied()
+                .min()
+                .unwrap_or(Distance::Infinite)
+        }
+    }
+}
+
+// Updates row, the distances from some vertex i, to include paths through vertex k. k_row
+// holds the distances from k.
+fn relax_row(row: &mut [Distance], k: usize, k_row: &[Distance]) {
+    let via = row[k];
+    if via == Distance::Infinite {
+        return;
+    }
+    for (dist, &k_dist) in row.iter_mut().zip(k_row) {
+        let new_dist = via + k_dist;
+        if new_dist < *dist {
+            *dist = new_dist;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apsp::tests::random_graph;
+    use crate::apsp::Dist;
+    use std::fs;
+    use xorshift::XorShift;
+
+    fn read_graph(filename: &str) -> Graph {
+        Graph::from_str(&fs::read_to_string(filename).unwrap())
+    }
+
+    // Runs every variant on graph, checking they agree with each other and with
+    // floyd_warshall, and returns the distances from in_place.
+    fn all_variants(graph: &Graph) -> FlatDistances {
+        let expected = graph.floyd_warshall();
+        assert_eq!(graph.floyd_warshall_in_place(), expected);
+        assert_eq!(graph.floyd_warshall_blocked(), expected);
+        assert_eq!(graph.floyd_warshall_parallel(), expected);
+
+        let mut in_place = FlatDistances::init(graph);
+        in_place.in_place();
+        let mut others = vec![];
+        for block_size in [1, 2, 3, 5, BLOCK_SIZE] {
+            let mut blocked = FlatDistances::init(graph);
+            blocked.blocked(block_size);
+            others.push(blocked);
+        }
+        for threads in [1, 2, 3, 7] {
+            let mut parallel = FlatDistances::init(graph);
+            parallel.parallel(threads);
+            others.push(parallel);
+        }
+        assert_eq!(in_place.min_dist(), expected);
+        for distances in others {
+            assert_eq!(distances.min_dist(), expected);
+            // The distances depend on the order of the updates when there's a negative cycle.
+            if expected != Distance::Infinite {
+                assert_eq!(distances, in_place);
+            }
+        }
+        in_place
+    }
+
+    #[test]
+    fn test_testgraph() {
+        let distances = all_variants(&read_graph("testgraph.txt"));
+        let finite = |row: [Dist; 9]| row.map(Distance::Finite).to_vec();
+        assert_eq!(distances.data, finite([0, 1, 0, 1, 0, -1, 2, 3, 0]));
+    }
+
+    #[test]
+    fn test_negative_cycle() {
+        let graph = read_graph("testgraph_negcycle.txt");
+        all_variants(&graph);
+        assert_eq!(graph.floyd_warshall_in_place(), Distance::Infinite);
+        assert_eq!(graph.floyd_warshall_blocked(), Distance::Infinite);
+        assert_eq!(graph.floyd_warshall_parallel(), Distance::Infinite);
+    }
+
+    #[test]
+    fn test_parallel_edges() {
+        // Only the shortest of the parallel edges from 1 to 2 counts, so the shortest path is
+        // 1 -> 2, not 2 -> 1.
+        let graph = Graph::from_str("2 4\n1 2 5\n1 2 -2\n1 2 4\n2 1 3\n");
+        assert_eq!(graph.floyd_warshall(), Distance::Finite(-2));
+        let distances = all_variants(&graph);
+        let finite = |row: [Dist; 4]| row.map(Distance::Finite).to_vec();
+        assert_eq!(distances.data, finite([0, -2, 3, 0]));
+    }
+
+    #[test]
+    fn test_long_negative_cycle_doesnt_overflow() {
+        // Every vertex of a complete graph with negative edges is on a negative cycle. Going
+        // around them over and over again makes the distances grow exponentially, which
+        // overflowed before the variants stopped at the first one.
+        let n = 64;
+        let mut input = format!("{} {}\n", n, n * (n - 1));
+        for tail in 1..=n {
+            for head in (1..=n).filter(|&head| head != tail) {
+                input.push_str(&format!("{} {} -1000000000\n", tail, head));
+            }
+        }
+        let graph = Graph::from_str(&input);
+        assert_eq!(graph.floyd_warshall_in_place(), Distance::Infinite);
+        assert_eq!(graph.floyd_warshall_blocked(), Distance::Infinite);
+        assert_eq!(graph.floyd_warshall_parallel(), Distance::Infinite);
+    }
+
+    #[test]
+    fn test_matches_johnson_on_random_graphs() {
+        let mut rng = XorShift::new(0x2545_f491_4f6c_dd1d);
+        for _ in 0..100 {
+            let n = 1 + rng.below(20);
+            let graph = random_graph(&mut rng, n, 3);
+
+            let distances = all_variants(&graph);
+            if let Ok(johnson) = graph.johnson_distances() {
+                let johnson: Vec<Distance> = johnson
+                    .iter()
+                    .skip(1)
+                    .flat_map(|row| &row[1..])
+                    .copied()
+                    .collect();
+                assert_eq!(distances.data, johnson);
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::apsp::{Dist, Distance, Graph};
+use crate::bellman_ford::NegativeCycle;
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+impl Graph {
+    // Computes the all pairs shortest paths with Johnson's algorithm and returns the same
+    // result as floyd_warshall: the length of the shortest path between any two vertices, or
+    // Infinite if there is a negative cycle.
+    pub fn johnson(&self) -> Distance {
+        match self.johnson_distances() {
+            Ok(distances) => distances
+                .iter()
+                .flatten()
+                .copied()
+                .min()
+                .unwrap_or(Distance::Infinite),
+            Err(_) => Distance::Infinite,
+        }
+    }
+
+    // Returns the distance between every pair of vertices, so distances[u][v] is the distance
+    // from u to v, or the negative cycle that makes them meaningless. Row and column 0 are
+    // Infinite, since there is no vertex 0.
+    //
+    // Johnson's algorithm runs Bellman-Ford once to find a potential for each vertex that
+    // makes every reweighted edge non-negative, and then Dijkstra's algorithm from every
+    // vertex. That takes O(nm log n) time, which beats the Θ(n³) of Floyd-Warshall on sparse
+    // graphs.
+    pub fn johnson_distances(&self) -> Result<Vec<Vec<Distance>>, NegativeCycle> {
+        let n = self.len();
+        // The distances from a new vertex with an edge of distance 0 to every vertex. These
+        // satisfy potential[head] <= potential[tail] + distance for every edge, so adding
+        // potential[tail] - potential[head] to each distance leaves none of them negative.
+        let mut potential = vec![Distance::Finite(0); n + 1];
+        self.relax_every_edge(&mut potential)?;
+        let potential: Vec<Dist> = potential
+            .into_iter()
+            .map(|distance| match distance {
+                Distance::Finite(distance) => distance,
+                Distance::Infinite => unreachable!("Every vertex starts at distance 0"),
+            })
+            .collect();
+
+        let mut distances = vec![vec![Distance::Infinite; n + 1]; n + 1];
+        for source in 1..=n {
+            let reweighted = self.reweighted_dijkstra(source, &potential);
+            // Reweighting adds potential[source] - potential[v] to every path from source to
+            // v, whichever path it is, so taking it back off gives the real distance.
+            for (v, distance) in reweighted.into_iter().enumerate().skip(1) {
+                if let Some(distance) = distance {
+                    distances[source][v] =
+                        Distance::Finite(distance - potential[source] + potential[v]);
+                }
+            }
+        }
+        Ok(distances)
+    }
+
+    // Runs Dijkstra's algorithm from source with each edge tail -> head made
+    // potential[tail] - potential[head] longer. Returns the reweighted distance to every
+    // vertex, or None for vertices that can't be reached.
+    fn reweighted_dijkstra(&self, source: usize, potential: &[Dist]) -> Vec<Option<Dist>> {
+        let mut distances: Vec<Option<Dist>> = vec![None; self.len() + 1];
+        let mut queue: BinaryHeap<Reverse<(Dist, usize)>> = BinaryHeap::new();
+        distances[source] = Some(0);
+        queue.push(Reverse((0, source)));
+
+        while let Some(Reverse((distance, tail))) = queue.pop() {
+            // Skip entries for paths that have since been beaten.
+            if Some(distance) != distances[tail] {
+                continue;
+            }
+            for edge in &self.0[tail] {
+                let reweighted = edge.distance + potential[tail] - potential[edge.head];
+                debug_assert!(reweighted >= 0, "Reweighted edges can't be negative");
+                let new_distance = distance + reweighted;
+                if distances[edge.head].is_none_or(|old| new_distance < old) {
+                    distances[edge.head] = Some(new_distance);
+                    queue.push(Reverse((new_distance, edge.head)));
+                }
+            }
+        }
+        distances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apsp::tests::random_graph;
+    use std::fs;
+    use xorshift::XorShift;
+
+    fn read_graph(filename: &str) -> Graph {
+        Graph::from_str(&fs::read_to_string(filename).unwrap())
+    }
+
+    #[test]
+    fn test_testgraph() {
+        let graph = read_graph("testgraph.txt");
+        assert_eq!(graph.johnson(), Distance::Finite(-1));
+        assert_eq!(graph.johnson(), graph.floyd_warshall());
+
+        let distances = graph.johnson_distances().unwrap();
+        let finite = |row: [Dist; 3]| {
+            let mut distances = vec![Distance::Infinite];
+            distances.extend(row.map(Distance::Finite));
+            distances
+        };
+        assert_eq!(
+            distances,
+            vec![
+                vec![Distance::Infinite; 4],
+                finite([0, 1, 0]),
+                finite([1, 0, -1]),
+                finite([2, 3, 0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_negative_cycle() {
+        let graph = read_graph("testgraph_negcycle.txt");
+        assert_eq!(graph.johnson(), Distance::Infinite);
+        assert_eq!(graph.johnson(), graph.floyd_warshall());
+        assert_eq!(graph.johnson_distances(), Err(NegativeCycle(vec![2, 4])));
+    }
+
+    #[test]
+    fn test_matches_floyd_warshall_on_random_graphs() {
+        let mut rng = XorShift::new(0x2545_f491_4f6c_dd1d);
+        for _ in 0..200 {
+            let n = 1 + rng.below(12);
+            let graph = random_graph(&mut rng, n, 4);
+
+            assert_eq!(graph.johnson(), graph.floyd_warshall());
+            // Every row should match running Bellman-Ford from that vertex.
+            match graph.johnson_distances() {
+                Ok(distances) => {
+                    for (source, row) in distances.iter().enumerate().skip(1) {
+                        let paths = graph.bellman_ford(source).unwrap();
+                        assert_eq!(*row, paths.distances);
+                    }
+                }
+                Err(_) => assert!((1..=n).any(|source| graph.bellman_ford(source).is_err())),
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod apsp;
+pub mod bellman_ford;
+pub mod floyd_warshall;
+pub mod johnson;